@@ -2,6 +2,7 @@ import { WorkerRecMessage } from "../worker/worker.interface";
 import { match, P } from "ts-pattern";
 import create from "zustand";
 import { CsvReaderStatus, Store, WorkerApi } from "./hooks.interface";
+import { cellToString } from "../utils";
 
 const workerApiDefault = {
   progress: 0,
@@ -31,7 +32,7 @@ const reducer = (state: WorkerApi, action: WorkerRecMessage): WorkerApi => {
       return { ...state, progress };
     })
     .with({ type: "chunk", payload: P.select() }, (payload) => {
-      const slice = payload.map((column) => column.split("DELIMITER_TOKEN"));
+      const slice = payload.map((column) => column.map(cellToString));
       return { ...state, slice };
     })
     .with({ type: "header", payload: P.select() }, (header) => {
@@ -44,7 +45,7 @@ const reducer = (state: WorkerApi, action: WorkerRecMessage): WorkerApi => {
       return { ...state, names, selectedId: index };
     })
     .with({ type: "distinct", payload: P.select() }, (payload) => {
-      return { ...state, equalToOptions: payload };
+      return { ...state, equalToOptions: payload.map(cellToString) };
     })
     .run();
 };
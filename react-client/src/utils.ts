@@ -1,3 +1,9 @@
+import { Cell } from "./worker/worker.interface";
+
+export function cellToString(cell: Cell): string {
+  return cell === null ? "" : String(cell);
+}
+
 export function inferColumnName(node: HTMLElement): HTMLElement {
   const childs = node.childNodes;
   return childs.length > 1
@@ -1,4 +1,5 @@
 import { Filter, Frame, PollSource } from "../../../wasm-lib";
+import { Cell } from "./worker.interface";
 
 export interface Wasm {
   newFrame: () => Frame;
@@ -8,7 +9,12 @@ export interface Wasm {
     header: boolean,
   ) => void,
   processStreamTail: (frame: Frame) => void;
-  addEqualtoFilter: (filter: Filter, frame: Frame, bytes: Uint8Array, column: string) => void;
+  addEqualtoFilter: (filter: Filter, frame: Frame, bytes: Uint8Array, column: string) => number;
+  addCondition: (filter: Filter, frame: Frame, text: string) => number;
+  removeCondition: (filter: Filter, frame: Frame, id: number) => boolean;
+  matchedCount: (filter: Filter, frame: Frame) => number;
+  aggregateFiltered: (filter: Filter, frame: Frame, column: string, name: string) => Cell;
+  valueCountsFiltered: (filter: Filter, frame: Frame, column: string, byValue: boolean) => Cell[][];
   newFilter: () => Filter;
   processCommand: (command: string, frame: Frame) => PollSource;
 }
@@ -16,7 +22,6 @@ export interface Wasm {
 export default class FrameJS {
   _tag: "frame" = "frame";
   private wasm?: Wasm;
-  private columnOrder: Map<string, number> = new Map();
   private _frame?: Frame;
   readonly id: number;
   readonly name: string;
@@ -32,16 +37,6 @@ export default class FrameJS {
     return this.wasm!.processStreamChunk(this._frame!, chunk, header);
   }
 
-  initColumnOrder() {
-    const columnNamesIter = this.header.entries();
-    let result = columnNamesIter.next();
-    while (!result.done) {
-      const [index, name] = result.value;
-      this.columnOrder.set(name, index);
-      result = columnNamesIter.next();
-    }
-  }
-
   processStreamTail() {
     return this.wasm!.processStreamTail(this._frame!);
   }
@@ -50,10 +45,46 @@ export default class FrameJS {
     return this._frame!.slice(offset, len);
   }
 
-  distinct(column: string): string {
+  addColumn(name: string, dtype: string, position: number) {
+    this._frame!.addColumn(name, dtype, position);
+  }
+
+  dropColumn(name: string) {
+    this._frame!.dropColumn(name);
+  }
+
+  renameColumn(name: string, newName: string) {
+    this._frame!.renameColumn(name, newName);
+  }
+
+  reorderColumns(names: string[]) {
+    this._frame!.reorderColumns(names);
+  }
+
+  castColumn(name: string, dtype: string) {
+    this._frame!.castColumn(name, dtype);
+  }
+
+  undo(): boolean {
+    return this._frame!.undo();
+  }
+
+  redo(): boolean {
+    return this._frame!.redo();
+  }
+
+  distinct(column: string): Cell[] {
     return this._frame!.distinct(column);
   }
 
+  aggregate(column: string, name: string): Cell {
+    return this._frame!.aggregate(column, name);
+  }
+
+  valueCounts(column: string, byValue: boolean): Cell[][] {
+    return this._frame!.valueCounts(column, byValue);
+  }
+
   get numberOfChunks() {
     return this._frame!.numberOfChunks;
   }
@@ -70,14 +70,13 @@ export class GlobalDataHandler {
 
   header({ id }: HeaderSendMessage["payload"]) {
     const frame = this.unsafeGetFrame(id) as FrameJS;
-    frame.initColumnOrder();
     const header = frame.header;
     this.worker!.postMessage({ type: "header", payload: header });
   }
 
   distinct({ id, column }: DistinctSendMessage["payload"]) {
     const frame = this.unsafeGetFrame(id) as FrameJS;
-    const slice = frame.distinct(column).split("DELIMITER_TOKEN");
+    const slice = frame.distinct(column);
     this.worker!.postMessage({ type: "distinct", payload: slice });
   }
 
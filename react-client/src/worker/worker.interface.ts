@@ -74,11 +74,13 @@ type ParsingRecMessage = {
   };
 };
 
-type ChunkRecMessage = { type: "chunk"; payload: string[] };
+export type Cell = number | bigint | boolean | string | null;
+
+type ChunkRecMessage = { type: "chunk"; payload: Cell[][] };
 type HeaderRecMessage = { type: "header"; payload: string[] };
 type NamesRecMessage = { type: "names"; payload: string[] };
 type SumColRecMessage = { type: "sumCol"; payload: string };
-type DistinctRecMessage = { type: "distinct"; payload: string[] };
+type DistinctRecMessage = { type: "distinct"; payload: Cell[] };
 type AddSourceRecMessage = {
   type: "addSource";
   payload: {
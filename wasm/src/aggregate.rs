@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+
+use ordered_float::OrderedFloat;
+
+use crate::{
+    column::Column,
+    series::{value::AnyValue, Primitive, PrimitiveSeries, SeriesEnum},
+};
+
+/// Reduction of a column to a single value. Nulls are skipped by every
+/// aggregation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Count,
+    CountDistinct,
+    Median,
+    /// Sample standard deviation.
+    Std,
+    /// Sample variance.
+    Var,
+    /// Value below which the given percentage of the cells fall, interpolated
+    /// linearly between the two nearest cells.
+    Percentile(OrderedFloat<f64>),
+}
+
+impl Aggregation {
+    /// Aggregation named `name`, percentiles take their rank separately.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "sum" => Some(Aggregation::Sum),
+            "avg" | "mean" | "average" => Some(Aggregation::Mean),
+            "min" => Some(Aggregation::Min),
+            "max" => Some(Aggregation::Max),
+            "count" => Some(Aggregation::Count),
+            "count distinct" => Some(Aggregation::CountDistinct),
+            "median" => Some(Aggregation::Median),
+            "std" => Some(Aggregation::Std),
+            "var" => Some(Aggregation::Var),
+            _ => None,
+        }
+    }
+
+    /// Aggregates the cells of `column` at `rows`, or the whole column. Counts
+    /// and bounds of a whole column are read from its stats.
+    pub fn apply(
+        self,
+        column: &Column,
+        rows: Option<&[usize]>,
+    ) -> Result<AnyValue<'static>, &'static str> {
+        let ret = match (self, rows) {
+            (Aggregation::Count, None) => AnyValue::I64(column.stats().count() as i64),
+            (Aggregation::Min, None) => column.stats().min().cloned().unwrap_or(AnyValue::Null),
+            (Aggregation::Max, None) => column.stats().max().cloned().unwrap_or(AnyValue::Null),
+            (Aggregation::CountDistinct, rows) => {
+                let distinct = match rows {
+                    Some(rows) => column.distinct_at(rows),
+                    None => column.distinct(),
+                };
+                let distinct = distinct.into_iter().filter(|cell| !cell.is_null());
+                AnyValue::I64(distinct.count() as i64)
+            }
+            (Aggregation::Count, Some(rows)) => {
+                let valid = rows
+                    .iter()
+                    .filter(|&&row| column.get(row).is_some_and(|cell| !cell.is_null()));
+                AnyValue::I64(valid.count() as i64)
+            }
+            (Aggregation::Min | Aggregation::Max, Some(rows)) => {
+                let cells = column.take(rows).into_iter().filter(|cell| !cell.is_null());
+                let cell = match self {
+                    Aggregation::Min => cells.min_by(compare),
+                    _ => cells.max_by(compare),
+                };
+                cell.map_or(AnyValue::Null, AnyValue::into_static)
+            }
+            (Aggregation::Sum, _) => sum(column, rows)?,
+            (Aggregation::Mean, _) => {
+                let values = numbers(column, rows)?;
+                match values.is_empty() {
+                    true => AnyValue::Null,
+                    false => AnyValue::F64(values.iter().sum::<f64>() / values.len() as f64),
+                }
+            }
+            (Aggregation::Var, _) => {
+                variance(&numbers(column, rows)?).map_or(AnyValue::Null, AnyValue::F64)
+            }
+            (Aggregation::Std, _) => variance(&numbers(column, rows)?)
+                .map_or(AnyValue::Null, |variance| AnyValue::F64(variance.sqrt())),
+            (Aggregation::Median, _) => percentile(numbers(column, rows)?, 50.0)?,
+            (Aggregation::Percentile(rank), _) => percentile(numbers(column, rows)?, rank.0)?,
+        };
+        Ok(ret)
+    }
+}
+
+fn compare(left: &AnyValue, right: &AnyValue) -> Ordering {
+    left.partial_cmp(right).unwrap_or(Ordering::Equal)
+}
+
+/// Values held by the cells of `series` at `rows`, or the whole series.
+fn values<'a, T: Primitive>(
+    series: &'a PrimitiveSeries<T>,
+    rows: Option<&'a [usize]>,
+) -> Box<dyn Iterator<Item = T> + 'a> {
+    match rows {
+        Some(rows) => Box::new(rows.iter().filter_map(|&row| {
+            let (chunk, index) = series.locate(row)?;
+            chunk.get(index)
+        })),
+        None => Box::new(series.iter().flatten()),
+    }
+}
+
+fn numbers(column: &Column, rows: Option<&[usize]>) -> Result<Vec<f64>, &'static str> {
+    let ret = match column.series() {
+        SeriesEnum::I32(series) => values(series, rows).map(f64::from).collect(),
+        SeriesEnum::I64(series) => values(series, rows).map(|v| v as f64).collect(),
+        SeriesEnum::I128(series) => values(series, rows).map(|v| v as f64).collect(),
+        SeriesEnum::F32(series) => values(series, rows).map(f64::from).collect(),
+        SeriesEnum::F64(series) => values(series, rows).collect(),
+        SeriesEnum::Bool(_) | SeriesEnum::Any(_) => {
+            return Err("Aggregation needs a numeric column")
+        }
+    };
+    Ok(ret)
+}
+
+/// Integers add up without overflowing as `I128`, floats as `F64`.
+fn sum(column: &Column, rows: Option<&[usize]>) -> Result<AnyValue<'static>, &'static str> {
+    let ret = match column.series() {
+        SeriesEnum::I32(series) => integer_sum(series, rows),
+        SeriesEnum::I64(series) => integer_sum(series, rows),
+        SeriesEnum::I128(series) => integer_sum(series, rows),
+        _ => AnyValue::F64(numbers(column, rows)?.into_iter().sum()),
+    };
+    Ok(ret)
+}
+
+fn integer_sum<T: Primitive + Into<i128>>(
+    series: &PrimitiveSeries<T>,
+    rows: Option<&[usize]>,
+) -> AnyValue<'static> {
+    AnyValue::I128(
+        values(series, rows)
+            .map(Into::into)
+            .fold(0, i128::saturating_add),
+    )
+}
+
+fn variance(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let squares = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+    Some(squares / (values.len() - 1) as f64)
+}
+
+fn percentile(mut values: Vec<f64>, rank: f64) -> Result<AnyValue<'static>, &'static str> {
+    if !(0.0..=100.0).contains(&rank) {
+        return Err("Percentile must be between 0 and 100");
+    }
+    if values.is_empty() {
+        return Ok(AnyValue::Null);
+    }
+
+    values.sort_by(f64::total_cmp);
+    let position = rank / 100.0 * (values.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    let weight = position - low as f64;
+    Ok(AnyValue::F64(
+        values[low] + (values[high] - values[low]) * weight,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Aggregation;
+    use crate::{series::value::AnyValue, ChunkFromJsBytes, Frame};
+
+    #[test]
+    fn aggregate() {
+        let bytes = "Name,Level,Weight\nFlareon,25,25.0\nVaporeon,,29.0\nJolteon,30,24.5\nEevee,5,6.5\nEspeon,30,26.5"
+            .as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+        let level = frame.find_by_name("Level");
+        let apply = |aggregation: Aggregation, rows| aggregation.apply(level, rows).unwrap();
+
+        assert_eq!(apply(Aggregation::Sum, None), AnyValue::I128(90));
+        assert_eq!(apply(Aggregation::Mean, None), AnyValue::F64(22.5));
+        assert_eq!(apply(Aggregation::Min, None), AnyValue::I32(5));
+        assert_eq!(apply(Aggregation::Max, None), AnyValue::I32(30));
+        assert_eq!(apply(Aggregation::Count, None), AnyValue::I64(4));
+        assert_eq!(apply(Aggregation::CountDistinct, None), AnyValue::I64(3));
+        assert_eq!(apply(Aggregation::Median, None), AnyValue::F64(27.5));
+        assert_eq!(
+            apply(Aggregation::Var, None),
+            AnyValue::F64(141.66666666666666)
+        );
+        assert_eq!(
+            apply(Aggregation::Percentile(25.0.into()), None),
+            AnyValue::F64(20.0)
+        );
+
+        // restricted to a set of rows
+        assert_eq!(
+            apply(Aggregation::Sum, Some(&[0, 1, 3])),
+            AnyValue::I128(30)
+        );
+        assert_eq!(apply(Aggregation::Std, Some(&[0])), AnyValue::Null);
+        assert_eq!(apply(Aggregation::Max, Some(&[])), AnyValue::Null);
+        assert_eq!(
+            apply(Aggregation::CountDistinct, Some(&[2, 1, 4, 7])),
+            AnyValue::I64(1)
+        );
+
+        let weight = frame.find_by_name("Weight");
+        assert_eq!(
+            Aggregation::Sum.apply(weight, None).unwrap(),
+            AnyValue::F64(111.5)
+        );
+        // NaN counts as a single value whatever the rows and their order
+        let bytes = "Ratio\n0.5\nNaN\n1.5\n0.5\nNaN\n-0.0\n0.0".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut ratios = Frame::new();
+        ratios.new_from_entry(chunk);
+        let ratio = ratios.find_by_name("Ratio");
+        let count = |rows| Aggregation::CountDistinct.apply(ratio, rows).unwrap();
+        assert_eq!(count(None), AnyValue::I64(4));
+        assert_eq!(count(Some(&[0, 1, 2, 3, 4, 5, 6])), AnyValue::I64(4));
+        assert_eq!(count(Some(&[1, 0, 4, 2, 6, 3, 5])), AnyValue::I64(4));
+        assert_eq!(count(Some(&[4, 0, 1])), AnyValue::I64(2));
+
+        let name = frame.find_by_name("Name");
+        assert_eq!(
+            Aggregation::Max.apply(name, None).unwrap(),
+            AnyValue::Str("Vaporeon".into())
+        );
+        assert_eq!(
+            Aggregation::Mean.apply(name, None),
+            Err("Aggregation needs a numeric column")
+        );
+        assert!(Aggregation::Percentile(101.0.into())
+            .apply(level, None)
+            .is_err());
+    }
+}
@@ -1,77 +1,62 @@
-use bitvec::slice::BitSlice;
+use std::cmp::Ordering;
+
+use bitvec::{prelude::BitVec, slice::BitSlice};
+use regex::Regex;
 
 use crate::{
+    memory::MemoryUsage,
     series::{
-        errors::{FilterResult, NonHashable},
-        SeriesTrait,
+        errors::{EditError, EditResult, FilterResult},
+        value::AnyValue,
+        CompareOp, SeriesEnum, SeriesTrait,
     },
+    stats::Stats,
     type_parser::Codes,
     Words,
 };
 
 pub struct Column {
-    series: Box<dyn SeriesTrait>,
+    series: SeriesEnum,
     name: String,
     dtype: Codes,
-}
-
-pub enum SeriesEnum {
-    I32(Box<Vec<Option<i32>>>),
-    I64(Box<Vec<Option<i64>>>),
-    I128(Box<Vec<Option<i128>>>),
-    F32(Box<Vec<Option<f32>>>),
-    F64(Box<Vec<Option<f64>>>),
-    Bool(Box<Vec<Option<bool>>>),
-    Any(Box<Vec<Option<String>>>),
+    stats: Stats,
+    /// Csv field the column is filled from while the file streams in, `None`
+    /// for columns added afterwards.
+    source: Option<usize>,
 }
 
 impl Column {
-    pub fn new(buffer: SeriesEnum, name: String, dtype: Codes) -> Self {
-        match buffer {
-            SeriesEnum::I32(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::I64(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::I128(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::F32(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::F64(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::Bool(series) => Self {
-                series,
-                name,
-                dtype,
-            },
-            SeriesEnum::Any(series) => Self {
-                series,
-                name,
-                dtype,
-            },
+    pub fn new(series: SeriesEnum, name: String, dtype: Codes) -> Self {
+        let stats = Stats::from_series(&series);
+        Self {
+            series,
+            name,
+            dtype,
+            stats,
+            source: None,
         }
     }
 
+    pub fn with_source(mut self, field: usize) -> Self {
+        self.source = Some(field);
+        self
+    }
+
+    pub fn source(&self) -> Option<usize> {
+        self.source
+    }
+
     pub fn len(&self) -> usize {
         self.series.len()
     }
 
-    pub fn series(&self) -> &dyn SeriesTrait {
-        self.series.as_ref()
+    /// Copy of the rows from `offset` onwards, with their own stats.
+    pub fn tail(&self, offset: usize) -> Column {
+        Column::new(self.series.tail(offset), self.name.clone(), self.dtype)
+    }
+
+    pub fn series(&self) -> &SeriesEnum {
+        &self.series
     }
 
     pub fn is_empty(&self) -> bool {
@@ -79,7 +64,101 @@ impl Column {
     }
 
     pub fn extend_from_words(&mut self, bytes: Words) {
-        self.series.extend_from_words(bytes)
+        let offset = self.series.len();
+        self.series.extend_from_words(bytes);
+        self.stats.update(&self.series, offset);
+    }
+
+    pub fn get(&self, index: usize) -> Option<AnyValue<'_>> {
+        self.series.get(index)
+    }
+
+    /// Parses `bytes` as the column type into the cell at `index`, returning the
+    /// value it replaced.
+    pub fn set(&mut self, index: usize, bytes: &[u8]) -> Result<AnyValue<'static>, EditError> {
+        self.replace_cell(index, |series| series.set(index, bytes))
+    }
+
+    /// Puts `value` back into the cell at `index`, returning the value it
+    /// replaced.
+    pub fn set_value(
+        &mut self,
+        index: usize,
+        value: &AnyValue,
+    ) -> Result<AnyValue<'static>, EditError> {
+        self.replace_cell(index, |series| series.set_value(index, value))
+    }
+
+    fn replace_cell(
+        &mut self,
+        index: usize,
+        set: impl FnOnce(&mut SeriesEnum) -> EditResult,
+    ) -> Result<AnyValue<'static>, EditError> {
+        let old = self
+            .series
+            .get(index)
+            .ok_or(EditError::OutOfBounds)?
+            .into_static();
+        set(&mut self.series)?;
+        self.stats.replace(&self.series, index, &old);
+        Ok(old)
+    }
+
+    /// Parses `bytes` into new rows at `position`. Stats are only folded
+    /// incrementally for appended rows, so they are rebuilt here.
+    pub fn insert_words(&mut self, position: usize, bytes: Words) {
+        self.series.insert_words(position, bytes);
+        self.stats = Stats::from_series(&self.series);
+    }
+
+    pub fn remove_rows(&mut self, mask: &BitSlice) -> SeriesEnum {
+        let removed = self.series.remove_rows(mask);
+        self.stats = Stats::from_series(&self.series);
+        removed
+    }
+
+    pub fn restore_rows(&mut self, mask: &BitSlice, rows: SeriesEnum) -> Result<(), EditError> {
+        self.series
+            .restore_rows(mask, rows)
+            .map_err(|_| EditError::InvalidValue)?;
+        self.stats = Stats::from_series(&self.series);
+        Ok(())
+    }
+
+    /// Copy of the column converted to `dtype` by reparsing the rendered cells,
+    /// cells the new type cannot represent become null.
+    pub fn cast(&self, dtype: Codes) -> Result<Column, EditError> {
+        let mut words = Words::default();
+        self.series
+            .slice(0, self.len())
+            .into_iter()
+            .for_each(|value| words.extend(value.to_string().as_bytes()));
+
+        let series = SeriesEnum::from_words(dtype, words).map_err(|_| EditError::UnknownType)?;
+        let mut column = Self::new(series, self.name.clone(), dtype);
+        column.source = self.source;
+        Ok(column)
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.series.memory_usage()
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn rechunk(&mut self) {
+        self.series.rechunk()
+    }
+
+    pub fn slice(&self, offset: usize, size: usize) -> Vec<AnyValue<'_>> {
+        self.series.slice(offset, size)
+    }
+
+    /// Cells at the given storage positions, null past the end.
+    pub fn take(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        self.series.take(rows)
     }
 
     pub fn join(&self, offset: usize, size: usize) -> String {
@@ -89,11 +168,7 @@ impl Column {
     pub fn sum(&self) -> Result<Self, &str> {
         let series = self.series.sum()?;
         let name = format!("Sum_of_{}", &self.name);
-        Ok(Self {
-            series,
-            name,
-            dtype: self.dtype,
-        })
+        Ok(Self::new(series, name, self.dtype))
     }
 
     pub fn first(&self) -> String {
@@ -104,36 +179,154 @@ impl Column {
         self.name.as_str()
     }
 
+    pub fn set_name(&mut self, name: String) -> String {
+        std::mem::replace(&mut self.name, name)
+    }
+
     pub fn dtype(&self) -> Codes {
         self.dtype
     }
 
-    pub fn equal_to(&self, other: &dyn SeriesTrait) -> FilterResult {
+    pub fn equal_to(&self, other: &SeriesEnum) -> FilterResult {
+        if let [value] = other.slice(0, 2).as_slice() {
+            if !self.stats.may_contain(value) {
+                return Ok(BitVec::repeat(false, self.len()));
+            }
+        }
         self.series.equal_to(other)
     }
 
-    pub fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.series.filter_join(mask, offset, size)
+    pub fn equal_within(&self, other: &SeriesEnum, tolerance: f64) -> FilterResult<'_> {
+        self.series.equal_within(other, tolerance)
+    }
+
+    pub fn compare(&self, op: CompareOp, other: &SeriesEnum) -> FilterResult<'_> {
+        if let [value] = other.slice(0, 2).as_slice() {
+            if let Some(all) = self.compare_bounds(op, value) {
+                return Ok(self.all_or_none(all));
+            }
+        }
+        self.series.compare(op, other)
+    }
+
+    pub fn between(&self, low: &SeriesEnum, high: &SeriesEnum) -> FilterResult<'_> {
+        if let ([low], [high]) = (low.slice(0, 2).as_slice(), high.slice(0, 2).as_slice()) {
+            let above = self.compare_bounds(CompareOp::GreaterEqual, low);
+            let below = self.compare_bounds(CompareOp::LessEqual, high);
+            match (above, below) {
+                (Some(false), _) | (_, Some(false)) => return Ok(self.all_or_none(false)),
+                (Some(true), Some(true)) => return Ok(self.all_or_none(true)),
+                _ => {}
+            }
+        }
+        self.series.between(low, high)
     }
 
-    pub fn distinct(&self) -> Result<String, NonHashable> {
+    /// Outcome of comparing every cell with `value` when the min and max settle
+    /// it: `Some(true)` when all the cells holding a value match, `Some(false)`
+    /// when none does.
+    fn compare_bounds(&self, op: CompareOp, value: &AnyValue) -> Option<bool> {
+        let low = self.stats.min()?.partial_cmp(value)?;
+        let high = self.stats.max()?.partial_cmp(value)?;
+        let outcome = match op {
+            CompareOp::Equal | CompareOp::NotEqual => {
+                let equal = match (low, high) {
+                    (Ordering::Greater, _) | (_, Ordering::Less) => Some(false),
+                    (Ordering::Equal, Ordering::Equal) => Some(true),
+                    _ => None,
+                };
+                equal.map(|equal| equal == (op == CompareOp::Equal))
+            }
+            op => {
+                let (low, high) = (op.matches(Some(low)), op.matches(Some(high)));
+                (low == high).then_some(low)
+            }
+        }?;
+        // NaN lies outside of the min and max and never matches
+        match self.dtype {
+            Codes::Float32 | Codes::Float64 if outcome => None,
+            _ => Some(outcome),
+        }
+    }
+
+    fn all_or_none(&self, all: bool) -> BitVec {
+        match all {
+            true => self.is_null(true),
+            false => BitVec::repeat(false, self.len()),
+        }
+    }
+
+    /// Null cells, or the cells holding a value when `negated`.
+    pub fn is_null(&self, negated: bool) -> BitVec {
+        if self.stats.null_count() == 0 {
+            return BitVec::repeat(negated, self.len());
+        }
+        match negated {
+            true => self.series.validity(),
+            false => !self.series.validity(),
+        }
+    }
+
+    pub fn matches(&self, regex: &Regex) -> FilterResult<'_> {
+        self.series.matches(regex)
+    }
+
+    pub fn distinct(&self) -> Vec<AnyValue<'_>> {
         self.series.distinct()
     }
+
+    pub fn distinct_at(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        self.series.distinct_at(rows)
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::type_parser::Codes;
+    use bitvec::prelude::BitVec;
+
+    use crate::{
+        series::{CompareOp, SeriesEnum},
+        type_parser::Codes,
+    };
 
-    use super::{Column, SeriesEnum};
+    use super::Column;
 
     #[test]
     fn first() {
         let v = vec![Some(1)];
-        let series = SeriesEnum::I32(Box::new(v));
+        let series = SeriesEnum::I32(v.into());
         let column = Column::new(series, "_".into(), Codes::Int32);
         let first = column.first();
 
         assert_eq!(first, "1".to_string());
     }
+
+    #[test]
+    fn bounds() {
+        let series = SeriesEnum::I32(vec![Some(25), None, Some(30)].into());
+        let column = Column::new(series, "Level".into(), Codes::Int32);
+        let value = |v: i32| SeriesEnum::I32(vec![Some(v)].into());
+        let ones = |mask: BitVec| mask.iter_ones().collect::<Vec<_>>();
+
+        let compare = |op, v| ones(column.compare(op, &value(v)).unwrap());
+        assert!(compare(CompareOp::Greater, 100).is_empty());
+        assert_eq!(compare(CompareOp::Greater, 10), vec![0, 2]);
+        assert_eq!(compare(CompareOp::LessEqual, 25), vec![0]);
+        assert_eq!(compare(CompareOp::NotEqual, 50), vec![0, 2]);
+        assert!(compare(CompareOp::Equal, 5).is_empty());
+
+        let between = |low, high| ones(column.between(&value(low), &value(high)).unwrap());
+        assert!(between(31, 40).is_empty());
+        assert_eq!(between(0, 30), vec![0, 2]);
+        assert_eq!(between(26, 30), vec![2]);
+
+        // NaN is neither above nor below any value
+        let series = SeriesEnum::F64(vec![Some(f64::NAN), Some(5.0)].into());
+        let column = Column::new(series, "Weight".into(), Codes::Float64);
+        let other = SeriesEnum::F64(vec![Some(4.0)].into());
+        assert_eq!(
+            ones(column.compare(CompareOp::Greater, &other).unwrap()),
+            vec![1]
+        );
+    }
 }
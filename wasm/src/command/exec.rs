@@ -1,18 +1,85 @@
-use super::parser::{parse_command, ParsedCommand};
-use crate::{filter::Filter, Frame};
+use super::{
+    group::{group_by, value_counts},
+    parser::{parse_command, ParsedCommand},
+    rank::top_rows,
+};
+use crate::{filter::Filter, predicate::Predicate, series::value::AnyValue, sort::SortKey, Frame};
 
 pub enum Slice {
     FilterSlice(Filter),
+    /// The whole frame, which now renders in sorted order.
+    Sorted,
+    /// Result of an aggregation.
+    Scalar(AnyValue<'static>),
+    /// New frame with a row per group or distinct value.
+    Grouped(Frame),
 }
 
-pub fn exec(input: &str, frame: &Frame) -> Result<Slice, &'static str> {
+/// Rows matching `predicate`, `None` to use every row.
+fn matching_rows(
+    frame: &Frame,
+    predicate: Option<Predicate>,
+) -> Result<Option<Vec<usize>>, &'static str> {
+    let Some(predicate) = predicate else {
+        return Ok(None);
+    };
+    let mut filter = Filter::default();
+    filter.add_condition(frame, predicate)?;
+    Ok(Some(filter.rows(frame).to_vec()))
+}
+
+pub fn exec(input: &str, frame: &mut Frame) -> Result<Slice, &'static str> {
     let (_, command) = parse_command(input).map_err(|_| "Cannot parse command")?;
     match command {
-        ParsedCommand::EqualFilter(column, value) => {
+        ParsedCommand::Filter(predicate) => {
             let mut filter = Filter::default();
-            filter.add_equalto_filter(frame, value.as_bytes(), column);
+            filter.add_condition(frame, predicate)?;
             Ok(Slice::FilterSlice(filter))
         }
-        _ => unimplemented!(""),
+        ParsedCommand::Sort(keys) => {
+            frame.sort(keys).map_err(|_| "Unknown column")?;
+            Ok(Slice::Sorted)
+        }
+        ParsedCommand::Aggregate {
+            aggregation,
+            column,
+            predicate,
+        } => {
+            let position = frame.column_index(&column).map_err(|_| "Unknown column")?;
+            let rows = matching_rows(frame, predicate)?;
+            let value = aggregation.apply(&frame.columns[position], rows.as_deref())?;
+            Ok(Slice::Scalar(value))
+        }
+        ParsedCommand::GroupBy { keys, aggregations } => {
+            Ok(Slice::Grouped(group_by(frame, &keys, &aggregations)?))
+        }
+        ParsedCommand::ValueCounts {
+            column,
+            order,
+            predicate,
+        } => {
+            let position = frame.column_index(&column).map_err(|_| "Unknown column")?;
+            let rows = matching_rows(frame, predicate)?;
+            let counts = value_counts(&frame.columns[position], rows.as_deref(), order);
+            Ok(Slice::Grouped(counts))
+        }
+        ParsedCommand::Top {
+            count,
+            column,
+            largest,
+            predicate,
+        } => {
+            let position = frame.column_index(&column).map_err(|_| "Unknown column")?;
+            let rows = matching_rows(frame, predicate)?;
+            let rows = top_rows(&frame.columns[position], rows.as_deref(), count, largest);
+            let rank = SortKey {
+                column,
+                descending: largest,
+                nulls_first: false,
+            };
+            Ok(Slice::FilterSlice(
+                Filter::from_rows(frame, &rows).ordered_by(rank),
+            ))
+        }
     }
 }
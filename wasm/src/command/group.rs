@@ -0,0 +1,331 @@
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::Entry, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use crate::{
+    aggregate::Aggregation, column::Column, series::value::AnyValue, series::SeriesEnum,
+    type_parser::Codes, Frame,
+};
+
+/// Aggregation computed for every group, a `count` without column counts rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupAggregation {
+    pub aggregation: Aggregation,
+    pub column: Option<String>,
+    /// Header of the result column, such as `sum(Sales)`.
+    pub name: String,
+}
+
+/// Values of the key columns for one row. Floats compare by bit pattern with
+/// `-0.0` folded onto `0.0` so that equality agrees with `Hash`.
+struct GroupKey<'a>(Vec<AnyValue<'a>>);
+
+impl PartialEq for GroupKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.iter().zip(other.0.iter()).all(|pair| match pair {
+            (AnyValue::F32(l), AnyValue::F32(r)) => (l + 0.0).to_bits() == (r + 0.0).to_bits(),
+            (AnyValue::F64(l), AnyValue::F64(r)) => (l + 0.0).to_bits() == (r + 0.0).to_bits(),
+            (l, r) => l == r,
+        })
+    }
+}
+
+impl Eq for GroupKey<'_> {}
+
+impl Hash for GroupKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Type of the column holding `aggregation` of a `dtype` column.
+fn result_type(aggregation: Aggregation, dtype: Codes) -> Codes {
+    match (aggregation, dtype) {
+        (Aggregation::Count | Aggregation::CountDistinct, _) => Codes::Int64,
+        (Aggregation::Min | Aggregation::Max, dtype) => dtype,
+        (Aggregation::Sum, Codes::Int32 | Codes::Int64 | Codes::Int128) => Codes::Int128,
+        _ => Codes::Float64,
+    }
+}
+
+fn into_column(values: &[AnyValue], name: String, dtype: Codes) -> Column {
+    let series =
+        SeriesEnum::from_values(dtype, values).expect("Values are of the result column type");
+    Column::new(series, name, dtype)
+}
+
+/// One row per distinct combination of `keys`, in order of first appearance,
+/// followed by a column for each aggregation.
+pub fn group_by(
+    frame: &Frame,
+    keys: &[String],
+    aggregations: &[GroupAggregation],
+) -> Result<Frame, &'static str> {
+    let position = |name: &str| frame.column_index(name).map_err(|_| "Unknown column");
+    let keys = keys
+        .iter()
+        .map(|key| position(key))
+        .collect::<Result<Vec<_>, _>>()?;
+    let targets = aggregations
+        .iter()
+        .map(|agg| agg.column.as_deref().map(position).transpose())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut groups = HashMap::<_, usize>::new();
+    let mut rows: Vec<Vec<usize>> = Vec::new();
+    for row in 0..frame.height() {
+        let key = keys
+            .iter()
+            .map(|&i| frame.columns[i].get(row).unwrap_or(AnyValue::Null))
+            .collect();
+        match groups.entry(GroupKey(key)) {
+            Entry::Occupied(entry) => rows[*entry.get()].push(row),
+            Entry::Vacant(entry) => {
+                entry.insert(rows.len());
+                rows.push(vec![row]);
+            }
+        }
+    }
+
+    let mut columns = keys
+        .iter()
+        .map(|&i| {
+            let column = &frame.columns[i];
+            let values = rows
+                .iter()
+                .map(|group| column.get(group[0]).unwrap_or(AnyValue::Null))
+                .collect::<Vec<_>>();
+            into_column(&values, column.name().into(), column.dtype())
+        })
+        .collect::<Vec<_>>();
+
+    for (agg, target) in aggregations.iter().zip(targets) {
+        let column = match target {
+            Some(i) => {
+                let column = &frame.columns[i];
+                let values = rows
+                    .iter()
+                    .map(|group| agg.aggregation.apply(column, Some(group)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                into_column(
+                    &values,
+                    agg.name.clone(),
+                    result_type(agg.aggregation, column.dtype()),
+                )
+            }
+            None if agg.aggregation == Aggregation::Count => {
+                let values = rows
+                    .iter()
+                    .map(|group| AnyValue::I64(group.len() as i64))
+                    .collect::<Vec<_>>();
+                into_column(&values, agg.name.clone(), Codes::Int64)
+            }
+            None => return Err("Aggregation needs a column"),
+        };
+        columns.push(column);
+    }
+
+    Ok(Frame::from_columns(columns))
+}
+
+/// Order of the rows returned by `value_counts`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountOrder {
+    /// Most frequent first, ties in value order.
+    Count,
+    /// Ascending values.
+    Value,
+}
+
+/// Values ascending with nulls last.
+fn compare_values(left: &AnyValue, right: &AnyValue) -> Ordering {
+    match (left.is_null(), right.is_null()) {
+        (false, false) => left.partial_cmp(right).unwrap_or(Ordering::Equal),
+        (left, right) => left.cmp(&right),
+    }
+}
+
+/// Each distinct value of `column` at `rows`, or in the whole column, with
+/// the number of cells holding it and their share in percent. Nulls are
+/// counted as a value of their own.
+pub fn value_counts(column: &Column, rows: Option<&[usize]>, order: CountOrder) -> Frame {
+    let cells = match rows {
+        Some(rows) => column.take(rows),
+        None => column.slice(0, column.len()),
+    };
+    let total = cells.len();
+
+    let mut positions = HashMap::<_, usize>::new();
+    let mut counts: Vec<(AnyValue, usize)> = Vec::new();
+    for cell in cells {
+        match positions.entry(GroupKey(vec![cell.clone()])) {
+            Entry::Occupied(entry) => counts[*entry.get()].1 += 1,
+            Entry::Vacant(entry) => {
+                entry.insert(counts.len());
+                counts.push((cell, 1usize));
+            }
+        }
+    }
+
+    match order {
+        CountOrder::Count => counts.sort_by(|(l_value, l_count), (r_value, r_count)| {
+            r_count
+                .cmp(l_count)
+                .then_with(|| compare_values(l_value, r_value))
+        }),
+        CountOrder::Value => counts.sort_by(|(l, _), (r, _)| compare_values(l, r)),
+    }
+
+    let percents = counts
+        .iter()
+        .map(|&(_, count)| AnyValue::F64(count as f64 * 100.0 / total as f64))
+        .collect::<Vec<_>>();
+    let (values, counts): (Vec<_>, Vec<_>) = counts
+        .into_iter()
+        .map(|(value, count)| (value, AnyValue::I64(count as i64)))
+        .unzip();
+
+    Frame::from_columns(vec![
+        into_column(&values, column.name().into(), column.dtype()),
+        into_column(&counts, "count".into(), Codes::Int64),
+        into_column(&percents, "percent".into(), Codes::Float64),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::parser::{parse_command, ParsedCommand},
+        series::value::AnyValue,
+        type_parser::Codes,
+        ChunkFromJsBytes, Frame,
+    };
+
+    use super::{group_by, value_counts, CountOrder};
+
+    #[test]
+    fn groups() {
+        // the last region is not valid utf8, so it is read as null
+        let bytes = b"Region,Year,Sales,Price\nNorth,2020,10,1.5\nSouth,2020,4,2.0\nNorth,2021,7,\nNorth,2020,5,2.5\n\xff,2020,1,1.0";
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let (_, command) = parse_command(
+            "GroupBy Region, Year agg sum(Sales), avg(Price), count(), count(Price), max(Region)",
+        )
+        .unwrap();
+        let ParsedCommand::GroupBy { keys, aggregations } = command else {
+            panic!("expected a group by")
+        };
+        let grouped = group_by(&frame, &keys, &aggregations).unwrap();
+
+        assert_eq!(grouped.height(), 4);
+        assert_eq!(
+            grouped
+                .columns
+                .iter()
+                .map(|column| (column.name(), column.dtype()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Region", Codes::Any),
+                ("Year", Codes::Int32),
+                ("sum(Sales)", Codes::Int128),
+                ("avg(Price)", Codes::Float64),
+                ("count()", Codes::Int64),
+                ("count(Price)", Codes::Int64),
+                ("max(Region)", Codes::Any),
+            ]
+        );
+        assert_eq!(
+            grouped.slice_values(0, 1),
+            vec![
+                vec![AnyValue::Str("North".into())],
+                vec![AnyValue::I32(2020)],
+                vec![AnyValue::I128(15)],
+                vec![AnyValue::F64(2.0)],
+                vec![AnyValue::I64(2)],
+                vec![AnyValue::I64(2)],
+                vec![AnyValue::Str("North".into())],
+            ]
+        );
+        // null keys form their own group, empty aggregations stay null
+        assert_eq!(
+            grouped.slice_values(2, 2)[0],
+            vec![AnyValue::Str("North".into()), AnyValue::Null]
+        );
+        assert_eq!(
+            grouped.slice_values(2, 2)[6],
+            vec![AnyValue::Str("North".into()), AnyValue::Null]
+        );
+        assert_eq!(
+            grouped.slice_values(2, 2)[3],
+            vec![AnyValue::Null, AnyValue::F64(1.0)]
+        );
+
+        let (_, command) = parse_command("GroupBy Region agg avg(Region)").unwrap();
+        let ParsedCommand::GroupBy { keys, aggregations } = command else {
+            panic!("expected a group by")
+        };
+        assert!(group_by(&frame, &keys, &aggregations).is_err());
+        let (_, command) = parse_command("GroupBy Zone agg count()").unwrap();
+        let ParsedCommand::GroupBy { keys, aggregations } = command else {
+            panic!("expected a group by")
+        };
+        assert_eq!(
+            group_by(&frame, &keys, &aggregations).err(),
+            Some("Unknown column")
+        );
+    }
+
+    #[test]
+    fn counts() {
+        let bytes =
+            "Type,Level,Weight\nFire,25,6.5\nWater,,29.0\nFire,30,6.5\nGrass,30,-0.0\nFire,5,0.0"
+                .as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let counts = value_counts(frame.find_by_name("Type"), None, CountOrder::Count);
+        assert_eq!(
+            counts.slice_values(0, 3),
+            vec![
+                vec![
+                    AnyValue::Str("Fire".into()),
+                    AnyValue::Str("Grass".into()),
+                    AnyValue::Str("Water".into())
+                ],
+                vec![AnyValue::I64(3), AnyValue::I64(1), AnyValue::I64(1)],
+                vec![
+                    AnyValue::F64(60.0),
+                    AnyValue::F64(20.0),
+                    AnyValue::F64(20.0)
+                ],
+            ]
+        );
+
+        let counts = value_counts(frame.find_by_name("Level"), None, CountOrder::Value);
+        assert_eq!(
+            counts.slice_values(0, 4)[0],
+            vec![
+                AnyValue::I32(5),
+                AnyValue::I32(25),
+                AnyValue::I32(30),
+                AnyValue::Null
+            ]
+        );
+        assert_eq!(counts.slice_values(2, 1)[1], vec![AnyValue::I64(2)]);
+
+        // zeros of either sign count as one value
+        let counts = value_counts(frame.find_by_name("Weight"), None, CountOrder::Count);
+        assert_eq!(counts.height(), 3);
+        assert_eq!(counts.slice_values(0, 1)[1], vec![AnyValue::I64(2)]);
+
+        let counts = value_counts(frame.find_by_name("Type"), Some(&[1, 3]), CountOrder::Count);
+        assert_eq!(counts.height(), 2);
+        assert_eq!(counts.slice_values(0, 1)[2], vec![AnyValue::F64(50.0)]);
+    }
+}
@@ -1,2 +1,4 @@
 pub mod exec;
+pub mod group;
 pub mod parser;
+pub mod rank;
@@ -1,48 +1,559 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until},
-    character::complete::multispace0,
-    sequence::delimited,
-    IResult, Parser,
+    bytes::complete::{tag, take_till1},
+    character::complete::{char, multispace0},
+    combinator::map,
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded},
+    IResult,
+};
+
+use super::group::{CountOrder, GroupAggregation};
+use crate::{
+    aggregate::Aggregation,
+    predicate::{Condition, Predicate, Test, TextOp},
+    sort::SortKey,
 };
 
 pub fn parse_instruction(input: &str) -> IResult<&str, &str> {
-    alt((tag("Filter"), tag("Average")))(input)
+    alt((
+        tag("Filter"),
+        tag("Aggregate"),
+        tag("Average"),
+        tag("GroupBy"),
+        tag("ValueCounts"),
+        tag("Top"),
+        tag("Bottom"),
+        tag("Sort"),
+    ))(input)
+}
+
+fn unknown_command() -> nom::Err<nom::error::Error<&'static str>> {
+    nom::Err::Error(nom::error::Error::new(
+        "Unknown command",
+        nom::error::ErrorKind::Tag,
+    ))
+}
+
+/// Operators spelled as words, the `i` variants ignore case.
+const KEYWORDS: [&str; 11] = [
+    "in",
+    "between",
+    "contains",
+    "icontains",
+    "starts with",
+    "istarts with",
+    "ends with",
+    "iends with",
+    "matches",
+    "is null",
+    "is not null",
+];
+
+/// Operators spelled as symbols, longest first.
+const SYMBOLS: [&str; 6] = ["!=", "<=", ">=", "=", "<", ">"];
+
+/// Single `<column> <symbol> <value>`, `<column> in (<value>, ...)`,
+/// `<column> between <value> and <value>`, `<column> contains <value>` (or
+/// `starts with`, `ends with`), `<column> matches /<regex>/` or
+/// `<column> is [not] null` condition. Equality takes an optional
+/// `within <tolerance>` for numeric columns.
+/// Values may be double quoted, doubling the quotes they contain.
+/// The column runs up to the first operator the rest of the condition parses
+/// after, so column names may contain spaces and operator words.
+pub fn parse_filter(input: &str) -> IResult<&str, Condition> {
+    keyword_boundaries(input)
+        .find_map(|at| {
+            let column = input[..at].trim();
+            let test = parse_test(&input[at..])?;
+            (!column.is_empty()).then(|| Condition {
+                column: column.into(),
+                test,
+            })
+        })
+        .map(|condition| ("", condition))
+        .ok_or_else(unknown_command)
+}
+
+/// Test spelled by the operator `text` starts with and the values following it.
+fn parse_test(text: &str) -> Option<Test> {
+    if let Some(keyword) = KEYWORDS
+        .into_iter()
+        .find(|keyword| starts_with_keyword(text, keyword))
+    {
+        let rest = &text[keyword.len()..];
+        return match keyword {
+            "in" => parse_list(rest),
+            "between" => parse_range(rest),
+            "matches" => parse_regex(rest),
+            "is null" | "is not null" => rest.trim().is_empty().then_some(Test::Null {
+                negated: keyword == "is not null",
+            }),
+            keyword => parse_text(keyword, rest),
+        };
+    }
+
+    let symbol = SYMBOLS
+        .into_iter()
+        .find(|symbol| text.starts_with(symbol))?;
+    let value = text[symbol.len()..].strip_prefix(' ')?;
+    if symbol == "=" {
+        if let Some(at) = find_keyword(value, "within") {
+            let tolerance = parse_value(&value[at + "within".len()..])?
+                .parse::<f64>()
+                .ok()
+                .filter(|tolerance| *tolerance >= 0.0)?;
+            return Some(Test::Near {
+                value: parse_value(&value[..at])?,
+                tolerance: tolerance.into(),
+            });
+        }
+    }
+
+    let value = parse_value(value)?;
+    match symbol {
+        "=" => Some(Test::Equal(value)),
+        symbol => Some(Test::Compare(symbol.parse().ok()?, value)),
+    }
+}
+
+/// Strips the quotes around a value, `None` when they are unbalanced.
+fn parse_value(text: &str) -> Option<String> {
+    let text = text.trim();
+    match text.strip_prefix('"') {
+        Some(quoted) => {
+            let inner = quoted.strip_suffix('"')?;
+            match inner.replace("\"\"", "").contains('"') {
+                true => None,
+                false => Some(inner.replace("\"\"", "\"")),
+            }
+        }
+        None => Some(text.into()),
+    }
+}
+
+/// Characters of `text` flagged when outside of quotes and `/regex/` literals.
+fn plain_chars(text: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+    let mut quoted = false;
+    let mut regex = false;
+    let mut escaped = false;
+    text.char_indices().map(move |(i, c)| {
+        let inside = quoted || regex;
+        match c {
+            '"' if !regex => quoted = !quoted,
+            '/' if regex && !escaped => regex = false,
+            '/' if !quoted && !regex && opens_regex(&text[..i]) => regex = true,
+            _ => {}
+        }
+        escaped = regex && c == '\\' && !escaped;
+        (i, c, !inside && !quoted && !regex)
+    })
+}
+
+/// A slash right after the `matches` keyword starts a regex literal.
+fn opens_regex(before: &str) -> bool {
+    let word = before.trim_end();
+    word.len() < before.len()
+        && word
+            .len()
+            .checked_sub("matches".len())
+            .and_then(|at| word.get(at..))
+            .is_some_and(|tail| tail.eq_ignore_ascii_case("matches"))
+}
+
+fn split_unquoted(text: &str, delimiter: char) -> Vec<&str> {
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (i, c, plain) in plain_chars(text) {
+        if plain && c == delimiter {
+            parts.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn parse_list(text: &str) -> Option<Test> {
+    let inner = text.trim().strip_prefix('(')?.strip_suffix(')')?;
+    if inner.trim().is_empty() {
+        return None;
+    }
+    let values = split_unquoted(inner, ',')
+        .into_iter()
+        .map(parse_value)
+        .collect::<Option<Vec<_>>>()?;
+    Some(Test::In(values))
 }
 
-pub fn parse_filter_column(symbol: &str) -> impl Parser<&str, &str, nom::error::Error<&str>> {
-    let column = take_until(symbol);
-    delimited(multispace0, column, multispace0)
+fn parse_range(text: &str) -> Option<Test> {
+    let at = find_keyword(text, "and")?;
+    let low = parse_value(&text[..at])?;
+    let high = parse_value(&text[at + "and".len()..])?;
+    Some(Test::Between(low, high))
 }
 
-pub fn parse_filter_symbol(input: &str) -> IResult<&str, &str> {
+fn parse_text(keyword: &str, text: &str) -> Option<Test> {
+    let case_insensitive = keyword.starts_with('i');
+    let op = match keyword.trim_start_matches('i') {
+        "contains" => TextOp::Contains,
+        "starts with" => TextOp::StartsWith,
+        _ => TextOp::EndsWith,
+    };
+    Some(Test::Text {
+        op,
+        pattern: parse_value(text)?,
+        case_insensitive,
+    })
+}
+
+/// `/<regex>/`, followed by `i` to ignore case, or a plain value.
+fn parse_regex(text: &str) -> Option<Test> {
+    let text = text.trim();
+    let (pattern, case_insensitive) = match text.strip_prefix('/') {
+        Some(literal) => {
+            let end = literal.rfind('/')?;
+            let case_insensitive = match &literal[end + 1..] {
+                "" => false,
+                "i" => true,
+                _ => return None,
+            };
+            (literal[..end].replace("\\/", "/"), case_insensitive)
+        }
+        None => (parse_value(text)?, false),
+    };
+    Some(Test::Text {
+        op: TextOp::Matches,
+        pattern,
+        case_insensitive,
+    })
+}
+
+/// `text` starts with the case insensitive `keyword` followed by a space, a
+/// parenthesis or nothing.
+fn starts_with_keyword(text: &str, keyword: &str) -> bool {
+    let Some(tail) = text
+        .get(..keyword.len())
+        .filter(|head| head.eq_ignore_ascii_case(keyword))
+        .map(|_| &text[keyword.len()..])
+    else {
+        return false;
+    };
+    tail.is_empty() || tail.starts_with(|c: char| c.is_whitespace() || c == '(')
+}
+
+fn keyword(word: &'static str) -> impl Fn(&str) -> IResult<&str, &str> {
+    move |input: &str| {
+        let trimmed = input.trim_start();
+        match starts_with_keyword(trimmed, word) {
+            true => Ok((&trimmed[word.len()..], &trimmed[..word.len()])),
+            false => Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::Tag,
+            ))),
+        }
+    }
+}
+
+/// Position of the first `keyword` following a space outside of quotes,
+/// regex literals and parentheses.
+fn find_keyword(text: &str, keyword: &str) -> Option<usize> {
+    keyword_boundaries(text).find(|&at| starts_with_keyword(&text[at..], keyword))
+}
+
+/// Positions of the words following a space outside of quotes, regex literals
+/// and parentheses.
+fn keyword_boundaries(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut depth = 0usize;
+    let mut space = false;
+    plain_chars(text).filter_map(move |(i, c, plain)| {
+        let boundary = space && plain && depth == 0 && !c.is_whitespace();
+        space = plain && c.is_whitespace();
+        match (c, plain) {
+            ('(', true) => depth += 1,
+            (')', true) => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        boundary.then_some(i)
+    })
+}
+
+/// A condition runs until an unmatched closing parenthesis or an `and` / `or`
+/// keyword, except the `and` of a `between`.
+fn condition_end(input: &str) -> usize {
+    let closing = {
+        let mut depth = 0usize;
+        plain_chars(input).find_map(|(i, c, plain)| {
+            match (c, plain) {
+                ('(', true) => depth += 1,
+                (')', true) if depth == 0 => return Some(i),
+                (')', true) => depth -= 1,
+                _ => {}
+            }
+            None
+        })
+    };
+    let input = &input[..closing.unwrap_or(input.len())];
+
+    let mut between = false;
+    keyword_boundaries(input)
+        .find(|&at| {
+            let rest = &input[at..];
+            match (starts_with_keyword(rest, "and"), between) {
+                (true, true) => {
+                    between = false;
+                    false
+                }
+                (true, false) => true,
+                _ => {
+                    between |= starts_with_keyword(rest, "between");
+                    starts_with_keyword(rest, "or")
+                }
+            }
+        })
+        .unwrap_or(input.len())
+}
+
+fn parse_condition(input: &str) -> IResult<&str, Predicate> {
+    let (text, tail) = input.split_at(condition_end(input));
+    let text = text.trim();
+    if text.starts_with('(') {
+        return Err(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Char,
+        )));
+    }
+    let (_, condition) = parse_filter(text)?;
+    Ok((tail, condition.into()))
+}
+
+fn parse_factor(input: &str) -> IResult<&str, Predicate> {
     alt((
-        parse_filter_column(" ="),
-        parse_filter_column(" <"),
-        parse_filter_column(" >"),
+        map(preceded(keyword("not"), parse_factor), |inner| {
+            Predicate::Not(Box::new(inner))
+        }),
+        delimited(
+            pair(multispace0, char('(')),
+            parse_predicate,
+            pair(multispace0, char(')')),
+        ),
+        parse_condition,
     ))(input)
 }
 
-pub fn parse_filter(input: &str) -> IResult<&str, ParsedCommand> {
-    let err = Err(nom::Err::Error(nom::error::Error::new(
-        "Unknown command",
-        nom::error::ErrorKind::Tag,
-    )));
+fn parse_term(input: &str) -> IResult<&str, Predicate> {
+    let (mut input, mut ret) = parse_factor(input)?;
+    while let Ok((tail, right)) = preceded(keyword("and"), parse_factor)(input) {
+        ret = Predicate::And(Box::new(ret), Box::new(right));
+        input = tail;
+    }
+    Ok((input, ret))
+}
 
-    let (input, column) = parse_filter_symbol(input)?;
-    let (value, symbol) = alt((tag("= "), tag("< "), tag("> ")))(input)?;
-    match symbol {
-        "= " => Ok(("", ParsedCommand::EqualFilter(column, value))),
-        _ => err,
+/// Conditions combined with `not`, `and`, `or` and parentheses, binding in
+/// that order.
+pub fn parse_predicate(input: &str) -> IResult<&str, Predicate> {
+    let (mut input, mut ret) = parse_term(input)?;
+    while let Ok((tail, right)) = preceded(keyword("or"), parse_term)(input) {
+        ret = Predicate::Or(Box::new(ret), Box::new(right));
+        input = tail;
     }
+    Ok((input, ret))
+}
+
+/// `text` without a trailing, case insensitive, whitespace separated `keyword`.
+fn strip_keyword<'a>(text: &'a str, keyword: &str) -> Option<&'a str> {
+    let split = text.len().checked_sub(keyword.len())?;
+    let (head, tail) = (text.get(..split)?, text.get(split..)?);
+    (tail.eq_ignore_ascii_case(keyword) && head.ends_with(char::is_whitespace))
+        .then(|| head.trim_end())
+}
+
+/// `<column> [asc|desc] [nulls first|nulls last]`, nulls go last by default.
+pub fn parse_sort_key(input: &str) -> IResult<&str, SortKey> {
+    let (tail, text) = take_till1(|c| c == ',')(input)?;
+    let text = text.trim();
+
+    let (text, nulls_first) = match strip_keyword(text, "nulls first") {
+        Some(text) => (text, true),
+        None => (strip_keyword(text, "nulls last").unwrap_or(text), false),
+    };
+    let (column, descending) = match strip_keyword(text, "desc") {
+        Some(column) => (column, true),
+        None => (strip_keyword(text, "asc").unwrap_or(text), false),
+    };
+
+    let key = SortKey {
+        column: column.into(),
+        descending,
+        nulls_first,
+    };
+    Ok((tail, key))
+}
+
+pub fn parse_sort(input: &str) -> IResult<&str, ParsedCommand> {
+    let (tail, keys) = separated_list1(char(','), parse_sort_key)(input)?;
+    Ok((tail, ParsedCommand::Sort(keys)))
+}
+
+/// Leading `count distinct`, `percentile <rank>` or single word aggregation.
+pub fn parse_aggregation(input: &str) -> IResult<&str, Aggregation> {
+    let input = input.trim_start();
+    if let Ok((tail, _)) = keyword("count distinct")(input) {
+        return Ok((tail, Aggregation::CountDistinct));
+    }
+    if let Ok((tail, _)) = keyword("percentile")(input) {
+        let tail = tail.trim_start();
+        let end = tail.find(char::is_whitespace).unwrap_or(tail.len());
+        let rank = tail[..end]
+            .parse::<f64>()
+            .ok()
+            .filter(|rank| (0.0..=100.0).contains(rank))
+            .ok_or_else(unknown_command)?;
+        return Ok((&tail[end..], Aggregation::Percentile(rank.into())));
+    }
+
+    let end = input.find(char::is_whitespace).unwrap_or(input.len());
+    let aggregation = Aggregation::from_name(&input[..end]).ok_or_else(unknown_command)?;
+    Ok((&input[end..], aggregation))
+}
+
+/// `<column> [where <predicate>]`, the rows the aggregation runs over.
+fn parse_aggregated(
+    aggregation: Aggregation,
+    input: &str,
+) -> Result<ParsedCommand, nom::Err<nom::error::Error<&str>>> {
+    let (column, predicate) = match find_keyword(input, "where") {
+        Some(at) => match parse_predicate(&input[at + "where".len()..])? {
+            (rest, predicate) if rest.trim().is_empty() => (&input[..at], Some(predicate)),
+            _ => return Err(unknown_command()),
+        },
+        None => (input, None),
+    };
+    let column = column.trim();
+    if column.is_empty() {
+        return Err(unknown_command());
+    }
+
+    Ok(ParsedCommand::Aggregate {
+        aggregation,
+        column: column.into(),
+        predicate,
+    })
+}
+
+/// `<aggregation>(<column>)`, the column may be left out of `count()`.
+fn parse_group_aggregation(text: &str) -> Option<GroupAggregation> {
+    let text = text.trim();
+    let open = text.find('(')?;
+    let column = text[open + 1..].strip_suffix(')')?.trim();
+    let name = text[..open].trim();
+    let aggregation = match parse_aggregation(name) {
+        Ok((rest, aggregation)) if rest.trim().is_empty() => aggregation,
+        _ => return None,
+    };
+
+    Some(GroupAggregation {
+        aggregation,
+        column: (!column.is_empty()).then(|| column.into()),
+        name: format!("{}({})", name, column),
+    })
+}
+
+/// `<key>, <key> agg <aggregation>(<column>), ...`
+fn parse_group_by(input: &str) -> Option<ParsedCommand> {
+    let at = find_keyword(input, "agg")?;
+    let keys = split_unquoted(&input[..at], ',')
+        .into_iter()
+        .map(|key| {
+            Some(key.trim())
+                .filter(|key| !key.is_empty())
+                .map(String::from)
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let aggregations = split_unquoted(&input[at + "agg".len()..], ',')
+        .into_iter()
+        .map(parse_group_aggregation)
+        .collect::<Option<Vec<_>>>()?;
+    Some(ParsedCommand::GroupBy { keys, aggregations })
+}
+
+/// `<column> [by count|by value] [where <predicate>]`, most frequent first by
+/// default.
+fn parse_value_counts(input: &str) -> Option<ParsedCommand> {
+    let (text, predicate) = match find_keyword(input, "where") {
+        Some(at) => match parse_predicate(&input[at + "where".len()..]) {
+            Ok((rest, predicate)) if rest.trim().is_empty() => (&input[..at], Some(predicate)),
+            _ => return None,
+        },
+        None => (input, None),
+    };
+    let text = text.trim();
+    let (column, order) = match strip_keyword(text, "by value") {
+        Some(column) => (column, CountOrder::Value),
+        None => (
+            strip_keyword(text, "by count").unwrap_or(text),
+            CountOrder::Count,
+        ),
+    };
+    (!column.is_empty()).then(|| ParsedCommand::ValueCounts {
+        column: column.into(),
+        order,
+        predicate,
+    })
+}
+
+/// `<count> by <column> [where <predicate>]`.
+fn parse_top(input: &str, largest: bool) -> Option<ParsedCommand> {
+    let (text, predicate) = match find_keyword(input, "where") {
+        Some(at) => match parse_predicate(&input[at + "where".len()..]) {
+            Ok((rest, predicate)) if rest.trim().is_empty() => (&input[..at], Some(predicate)),
+            _ => return None,
+        },
+        None => (input, None),
+    };
+    let text = text.trim_start();
+    let end = text.find(char::is_whitespace)?;
+    let count = text[..end].parse().ok()?;
+    let (column, _) = keyword("by")(&text[end..]).ok()?;
+    let column = column.trim();
+
+    (!column.is_empty()).then(|| ParsedCommand::Top {
+        count,
+        column: column.into(),
+        largest,
+        predicate,
+    })
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub enum ParsedCommand<'a> {
-    EqualFilter(&'a str, &'a str),
-    LessFilter(&'a str, &'a str),
-    GreaterFilter(&'a str, &'a str),
-    Average,
+pub enum ParsedCommand {
+    Filter(Predicate),
+    Sort(Vec<SortKey>),
+    /// Single value computed over a column, restricted to the rows matching
+    /// `predicate` when given.
+    Aggregate {
+        aggregation: Aggregation,
+        column: String,
+        predicate: Option<Predicate>,
+    },
+    GroupBy {
+        keys: Vec<String>,
+        aggregations: Vec<GroupAggregation>,
+    },
+    ValueCounts {
+        column: String,
+        order: CountOrder,
+        predicate: Option<Predicate>,
+    },
+    /// The `count` rows with the largest values of `column`, or the smallest
+    /// unless `largest`.
+    Top {
+        count: usize,
+        column: String,
+        largest: bool,
+        predicate: Option<Predicate>,
+    },
 }
 
 pub fn parse_command(input: &str) -> IResult<&str, ParsedCommand> {
@@ -53,10 +564,33 @@ pub fn parse_command(input: &str) -> IResult<&str, ParsedCommand> {
     )));
 
     match keyword {
-        "Filter" => {
-            let (_, command) = parse_filter(tail)?;
+        "Filter" => match parse_predicate(tail)? {
+            (rest, predicate) if rest.trim().is_empty() => {
+                Ok((keyword, ParsedCommand::Filter(predicate)))
+            }
+            _ => err,
+        },
+        "Sort" => {
+            let (_, command) = parse_sort(tail)?;
             Ok((keyword, command))
         }
+        "Aggregate" => {
+            let (tail, aggregation) = parse_aggregation(tail)?;
+            Ok((keyword, parse_aggregated(aggregation, tail)?))
+        }
+        "Average" => Ok((keyword, parse_aggregated(Aggregation::Mean, tail)?)),
+        "GroupBy" => match parse_group_by(tail) {
+            Some(command) => Ok((keyword, command)),
+            None => err,
+        },
+        "ValueCounts" => match parse_value_counts(tail) {
+            Some(command) => Ok((keyword, command)),
+            None => err,
+        },
+        "Top" | "Bottom" => match parse_top(tail, keyword == "Top") {
+            Some(command) => Ok((keyword, command)),
+            None => err,
+        },
         _ => err,
     }
 }
@@ -64,16 +598,340 @@ pub fn parse_command(input: &str) -> IResult<&str, ParsedCommand> {
 #[cfg(test)]
 mod test {
     use super::parse_instruction;
-    use crate::command::parser::{parse_filter, ParsedCommand};
+    use crate::{
+        aggregate::Aggregation,
+        command::group::{CountOrder, GroupAggregation},
+        command::parser::{parse_command, parse_filter, ParsedCommand},
+        predicate::{Condition, Predicate, Test, TextOp},
+        series::CompareOp,
+        sort::SortKey,
+    };
+
+    fn condition(column: &str, test: Test) -> Predicate {
+        Condition {
+            column: column.into(),
+            test,
+        }
+        .into()
+    }
 
     #[test]
     fn command() {
         let (tail, command) = parse_instruction("Filter Type 1 = Fire").unwrap();
-        let ret = match command {
-            "Filter" => parse_filter(tail).unwrap().1,
-            _ => ParsedCommand::Average,
+        assert_eq!(command, "Filter");
+        let (_, ret) = parse_filter(tail.trim()).unwrap();
+        assert_eq!(ret.column, "Type 1");
+        assert_eq!(ret.test, Test::Equal("Fire".into()));
+
+        let (_, command) = parse_command("Filter Total >= 500").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(condition(
+                "Total",
+                Test::Compare(CompareOp::GreaterEqual, "500".into())
+            ))
+        );
+        let (_, command) = parse_command("Filter Type 2 != Flying").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(condition(
+                "Type 2",
+                Test::Compare(CompareOp::NotEqual, "Flying".into())
+            ))
+        );
+
+        // column names may contain operator words, symbols within values do
+        // not end the column
+        let cases = [
+            (
+                "Time in Queue > 5",
+                condition(
+                    "Time in Queue",
+                    Test::Compare(CompareOp::Greater, "5".into()),
+                ),
+            ),
+            (
+                "Contains Flag contains x",
+                condition(
+                    "Contains Flag",
+                    Test::Text {
+                        op: TextOp::Contains,
+                        pattern: "x".into(),
+                        case_insensitive: false,
+                    },
+                ),
+            ),
+            (
+                "Status In Progress is not null",
+                condition("Status In Progress", Test::Null { negated: true }),
+            ),
+            (
+                "Name in ('a<b', c)",
+                condition("Name", Test::In(vec!["'a<b'".into(), "c".into()])),
+            ),
+            (
+                "Note = \"x > y\"",
+                condition("Note", Test::Equal("x > y".into())),
+            ),
+        ];
+        for (text, expected) in cases {
+            let (_, condition) = parse_filter(text).unwrap();
+            assert_eq!(Predicate::from(condition), expected, "{text}");
+        }
+        assert!(parse_filter("= 5").is_err());
+    }
+
+    #[test]
+    fn compound() {
+        let (_, command) =
+            parse_command("Filter (Type = Fire OR Type = Water) and not Legendary = true").unwrap();
+        let either = Predicate::Or(
+            Box::new(condition("Type", Test::Equal("Fire".into()))),
+            Box::new(condition("Type", Test::Equal("Water".into()))),
+        );
+        let legendary = condition("Legendary", Test::Equal("true".into()));
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(Predicate::And(
+                Box::new(either),
+                Box::new(Predicate::Not(Box::new(legendary)))
+            ))
+        );
+
+        // and binds tighter than or, column names may start with a keyword
+        let (_, command) = parse_command("Filter Notes = a or Order = 1 and Total > 5").unwrap();
+        let ParsedCommand::Filter(Predicate::Or(left, right)) = command else {
+            panic!("expected or at the top")
+        };
+        assert_eq!(*left, condition("Notes", Test::Equal("a".into())));
+        assert!(matches!(*right, Predicate::And(_, _)));
+
+        let (_, command) = parse_command(
+            r#"Filter Level between 10 and 30 and Name in ("Mr. Mime", "Farfetch""d, Galar") or Name = "A (and) B""#,
+        )
+        .unwrap();
+        let range = condition("Level", Test::Between("10".into(), "30".into()));
+        let names = condition(
+            "Name",
+            Test::In(vec!["Mr. Mime".into(), "Farfetch\"d, Galar".into()]),
+        );
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(Predicate::Or(
+                Box::new(Predicate::And(Box::new(range), Box::new(names))),
+                Box::new(condition("Name", Test::Equal("A (and) B".into())))
+            ))
+        );
+
+        let text = |op, pattern: &str, case_insensitive| Test::Text {
+            op,
+            pattern: pattern.into(),
+            case_insensitive,
+        };
+        let (_, command) =
+            parse_command(r#"Filter Name matches /^(Mr|Mime) or \/x/i or Name istarts with "Por""#)
+                .unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(Predicate::Or(
+                Box::new(condition(
+                    "Name",
+                    text(TextOp::Matches, "^(Mr|Mime) or /x", true)
+                )),
+                Box::new(condition("Name", text(TextOp::StartsWith, "Por", true)))
+            ))
+        );
+        let (_, command) = parse_command("Filter Name contains eon and Type ends with re").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(Predicate::And(
+                Box::new(condition("Name", text(TextOp::Contains, "eon", false))),
+                Box::new(condition("Type", text(TextOp::EndsWith, "re", false)))
+            ))
+        );
+
+        let (_, command) = parse_command("Filter Level is null or not (Type IS NOT NULL)").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(Predicate::Or(
+                Box::new(condition("Level", Test::Null { negated: false })),
+                Box::new(Predicate::Not(Box::new(condition(
+                    "Type",
+                    Test::Null { negated: true }
+                ))))
+            ))
+        );
+
+        let (_, command) = parse_command("Filter Weight = 6.9 within 0.05").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Filter(condition(
+                "Weight",
+                Test::Near {
+                    value: "6.9".into(),
+                    tolerance: 0.05.into()
+                }
+            ))
+        );
+        assert!(parse_command("Filter Weight = 6.9 within -1").is_err());
+        assert!(parse_command("Filter Level is null 5").is_err());
+        assert!(parse_command("Filter Name matches /eon/g").is_err());
+        assert!(parse_command(r#"Filter Name = "Mime"#).is_err());
+        assert!(parse_command("Filter Level in ()").is_err());
+        assert!(parse_command("Filter (Type = Fire").is_err());
+        assert!(parse_command("Filter Type = Fire)").is_err());
+    }
+
+    #[test]
+    fn sort() {
+        let (_, command) = parse_command("Sort Price desc, Type 1 nulls first,Name ASC").unwrap();
+        let key = |column: &str, descending, nulls_first| SortKey {
+            column: column.into(),
+            descending,
+            nulls_first,
         };
-        assert_eq!(ret, ParsedCommand::EqualFilter("Type 1", "Fire"));
+        assert_eq!(
+            command,
+            ParsedCommand::Sort(vec![
+                key("Price", true, false),
+                key("Type 1", false, true),
+                key("Name", false, false),
+            ])
+        );
+    }
+
+    #[test]
+    fn aggregate() {
+        let (_, command) = parse_command("Aggregate sum Total").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Aggregate {
+                aggregation: Aggregation::Sum,
+                column: "Total".into(),
+                predicate: None,
+            }
+        );
+        let (_, command) =
+            parse_command("Aggregate count distinct Type 1 where Legendary = true").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Aggregate {
+                aggregation: Aggregation::CountDistinct,
+                column: "Type 1".into(),
+                predicate: Some(condition("Legendary", Test::Equal("true".into()))),
+            }
+        );
+        let (_, command) = parse_command("Aggregate percentile 90 Speed").unwrap();
+        assert!(matches!(
+            command,
+            ParsedCommand::Aggregate {
+                aggregation: Aggregation::Percentile(rank),
+                ..
+            } if rank == 90.0
+        ));
+        let (_, command) =
+            parse_command("Average Attack where Type = Fire or Type = Water").unwrap();
+        assert!(matches!(
+            command,
+            ParsedCommand::Aggregate {
+                aggregation: Aggregation::Mean,
+                predicate: Some(Predicate::Or(_, _)),
+                ..
+            }
+        ));
+
+        assert!(parse_command("Aggregate total Attack").is_err());
+        assert!(parse_command("Aggregate percentile 120 Attack").is_err());
+        assert!(parse_command("Aggregate max").is_err());
+        assert!(parse_command("Average Attack where Type =").is_err());
+    }
+
+    #[test]
+    fn group_by() {
+        let (_, command) =
+            parse_command("GroupBy Region, Year agg sum(Sales), avg(Price), count()").unwrap();
+        let aggregation = |aggregation, column: Option<&str>, name: &str| GroupAggregation {
+            aggregation,
+            column: column.map(String::from),
+            name: name.into(),
+        };
+        assert_eq!(
+            command,
+            ParsedCommand::GroupBy {
+                keys: vec!["Region".into(), "Year".into()],
+                aggregations: vec![
+                    aggregation(Aggregation::Sum, Some("Sales"), "sum(Sales)"),
+                    aggregation(Aggregation::Mean, Some("Price"), "avg(Price)"),
+                    aggregation(Aggregation::Count, None, "count()"),
+                ],
+            }
+        );
+        let (_, command) =
+            parse_command("GroupBy Type 1 agg count distinct(Type 2), percentile 90 (Speed)")
+                .unwrap();
+        let ParsedCommand::GroupBy { aggregations, .. } = command else {
+            panic!("expected a group by")
+        };
+        assert_eq!(aggregations[0].aggregation, Aggregation::CountDistinct);
+        assert_eq!(aggregations[1].name, "percentile 90(Speed)");
+
+        assert!(parse_command("GroupBy Region").is_err());
+        assert!(parse_command("GroupBy Region agg total(Sales)").is_err());
+        assert!(parse_command("GroupBy Region, agg sum(Sales)").is_err());
+        assert!(parse_command("GroupBy Region agg sum Sales").is_err());
+    }
+
+    #[test]
+    fn value_counts() {
+        let (_, command) = parse_command("ValueCounts Type 1").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::ValueCounts {
+                column: "Type 1".into(),
+                order: CountOrder::Count,
+                predicate: None,
+            }
+        );
+        let (_, command) =
+            parse_command("ValueCounts Type 1 by value where Generation = 1").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::ValueCounts {
+                column: "Type 1".into(),
+                order: CountOrder::Value,
+                predicate: Some(condition("Generation", Test::Equal("1".into()))),
+            }
+        );
+        assert!(parse_command("ValueCounts ").is_err());
+        assert!(parse_command("ValueCounts Type where").is_err());
+    }
+
+    #[test]
+    fn top() {
+        let (_, command) = parse_command("Top 10 by Amount").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Top {
+                count: 10,
+                column: "Amount".into(),
+                largest: true,
+                predicate: None,
+            }
+        );
+        let (_, command) = parse_command("Bottom 5 BY Total Score where Active = true").unwrap();
+        assert_eq!(
+            command,
+            ParsedCommand::Top {
+                count: 5,
+                column: "Total Score".into(),
+                largest: false,
+                predicate: Some(condition("Active", Test::Equal("true".into()))),
+            }
+        );
+        assert!(parse_command("Top by Amount").is_err());
+        assert!(parse_command("Top -1 by Amount").is_err());
+        assert!(parse_command("Top 3 Amount").is_err());
+        assert!(parse_command("Top 3 by").is_err());
     }
 
     #[test]
@@ -0,0 +1,161 @@
+use std::{cmp::Ordering, cmp::Reverse, collections::BinaryHeap};
+
+use crate::{column::Column, series::value::AnyValue};
+
+/// Cell competing for a place in the top rows. Greater means better ranked:
+/// larger values when looking for the `largest`, smaller ones otherwise, and
+/// the earlier row on ties. NaN ranks below every number either way.
+struct Ranked<'a> {
+    value: AnyValue<'a>,
+    row: usize,
+    largest: bool,
+}
+
+impl Ord for Ranked<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ord = match (self.value.is_nan(), other.value.is_nan()) {
+            (false, false) => {
+                let ord = self
+                    .value
+                    .partial_cmp(&other.value)
+                    .unwrap_or(Ordering::Equal);
+                match self.largest {
+                    true => ord,
+                    false => ord.reverse(),
+                }
+            }
+            (nan, other_nan) => other_nan.cmp(&nan),
+        };
+        ord.then_with(|| other.row.cmp(&self.row))
+    }
+}
+
+impl PartialOrd for Ranked<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Ranked<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Ranked<'_> {}
+
+/// Storage positions of the `count` largest, or smallest, cells of `column`
+/// among `rows`, or the whole column, best ranked first. Cells are read one at
+/// a time and only `count` of them are kept around, nulls never make it in.
+pub fn top_rows(
+    column: &Column,
+    rows: Option<&[usize]>,
+    count: usize,
+    largest: bool,
+) -> Vec<usize> {
+    let (rows, all) = match rows {
+        Some(rows) => (rows, 0..0),
+        None => (&[][..], 0..column.len()),
+    };
+
+    let mut heap = BinaryHeap::with_capacity(count + 1);
+    for row in rows.iter().copied().chain(all) {
+        let Some(value) = column.get(row).filter(|value| !value.is_null()) else {
+            continue;
+        };
+        heap.push(Reverse(Ranked {
+            value,
+            row,
+            largest,
+        }));
+        if heap.len() > count {
+            heap.pop();
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| ranked.row)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::top_rows;
+    use crate::{
+        command::{
+            exec::{exec, Slice},
+            parser::parse_predicate,
+        },
+        ChunkFromJsBytes, Frame,
+    };
+
+    #[test]
+    fn top() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,\nJolteon,30\nEevee,5\nEspeon,30\nUmbreon,12"
+            .as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+        let level = frame.find_by_name("Level");
+
+        assert_eq!(top_rows(level, None, 3, true), vec![2, 4, 0]);
+        assert_eq!(top_rows(level, None, 2, false), vec![3, 5]);
+        assert_eq!(top_rows(level, None, 10, false), vec![3, 5, 0, 2, 4]);
+        assert_eq!(top_rows(level, Some(&[0, 1, 3, 5]), 2, true), vec![0, 5]);
+        assert!(top_rows(level, None, 0, true).is_empty());
+
+        let name = frame.find_by_name("Name");
+        assert_eq!(top_rows(name, None, 1, true), vec![1]);
+
+        let Ok(Slice::FilterSlice(mut filter)) =
+            exec("Top 2 by Level where Name != Jolteon", &mut frame)
+        else {
+            panic!("expected a filtered view")
+        };
+        assert_eq!(filter.join(&frame, 0, 5)[0], "EspeonDELIMITER_TOKENFlareon");
+        let Ok(Slice::FilterSlice(mut bottom)) = exec("Bottom 3 by Level", &mut frame) else {
+            panic!("expected a filtered view")
+        };
+        assert_eq!(
+            bottom.join(&frame, 0, 5)[0],
+            "EeveeDELIMITER_TOKENUmbreonDELIMITER_TOKENFlareon"
+        );
+
+        // rows streamed in later are not part of the selection, conditions
+        // only narrow it
+        frame.append(b"Leafeon,60\nGlaceon,70", false);
+        frame.append_remainder();
+        assert_eq!(filter.matched_count(&frame), 2);
+        let (_, predicate) = parse_predicate("Name starts with E").unwrap();
+        let id = filter.add_condition(&frame, predicate).unwrap();
+        assert_eq!(filter.join(&frame, 0, 5)[0], "Espeon");
+        assert!(filter.remove_condition(&frame, id).unwrap());
+        assert_eq!(filter.matched_count(&frame), 2);
+
+        assert!(exec("Bottom 2 by Kind", &mut frame).is_err());
+    }
+
+    #[test]
+    fn nan_ranks_last() {
+        let bytes = "Name,Weight\nFlareon,25.0\nVaporeon,NaN\nJolteon,24.5\nEevee,NaN\nEspeon,26.5"
+            .as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+        let weight = frame.find_by_name("Weight");
+
+        assert_eq!(top_rows(weight, None, 2, true), vec![4, 0]);
+        assert_eq!(top_rows(weight, None, 2, false), vec![2, 0]);
+        assert_eq!(top_rows(weight, Some(&[3, 1, 2]), 2, true), vec![2, 1]);
+        assert_eq!(top_rows(weight, None, 5, false), vec![2, 0, 4, 1, 3]);
+
+        let Ok(Slice::FilterSlice(mut top)) = exec("Top 4 by Weight", &mut frame) else {
+            panic!("expected a filtered view")
+        };
+        assert_eq!(
+            top.join(&frame, 0, 5)[0],
+            "EspeonDELIMITER_TOKENFlareonDELIMITER_TOKENJolteonDELIMITER_TOKENVaporeon"
+        );
+    }
+}
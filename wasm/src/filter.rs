@@ -1,54 +1,293 @@
+use std::{cell::Cell, rc::Rc};
+
 use bitvec::{prelude::BitVec, slice::BitSlice};
-use js_sys::JsString;
 use wasm_bindgen::prelude::wasm_bindgen;
 
 use crate::{
     csv_parser::FieldIter,
-    series::SeriesTrait,
-    type_parser::{parse_type, parse_utf8, Codes},
+    predicate::{Condition, Predicate, Test},
+    rows::RowEdit,
+    series::{
+        coerce::common_type,
+        errors::WrongType,
+        value::{join_values, AnyValue},
+        CompareOp, SeriesEnum, SeriesTrait,
+    },
+    sort::SortKey,
+    type_parser::Codes,
     Frame, Words,
 };
 
-pub fn single_buffer_into_col_trait(bytes: &[u8], code: Codes) -> Box<dyn SeriesTrait> {
+/// Parses the comma separated values in `bytes` as the type `code`.
+pub fn single_buffer_into_series(bytes: &[u8], code: Codes) -> Result<SeriesEnum, WrongType> {
     let mut commands = Words::default();
     let words = FieldIter::from_bytes(bytes);
     for word in words {
         commands.extend(word);
     }
 
-    match code {
-        Codes::Int32 => Box::new(parse_type::<i32>(commands)),
-        Codes::Any => Box::new(parse_utf8(commands)),
-        _ => panic!("Unimplemented"),
+    SeriesEnum::from_words(code, commands)
+}
+
+/// Parses a single literal as the column type. A number that does not fit a
+/// numeric column is read as the first wider type it parses as, to be promoted
+/// when compared, any other literal that does not fit is an error.
+pub fn literal_into_series(bytes: &[u8], code: Codes) -> Result<SeriesEnum, WrongType> {
+    let parse = |code| {
+        let mut words = Words::default();
+        words.extend(bytes);
+        SeriesEnum::from_words(code, words).expect("Column types are concrete")
+    };
+
+    let series = parse(code);
+    if series.validity().any() {
+        return Ok(series);
     }
+    [Codes::Int64, Codes::Int128, Codes::Float64]
+        .into_iter()
+        .filter(|&wider| wider > code && common_type(code, wider).is_ok())
+        .map(parse)
+        .find(|wider| wider.validity().any())
+        .ok_or(WrongType)
 }
 
+/// Rows matching every condition added to the filter.
 #[wasm_bindgen]
 #[derive(Default)]
 pub struct Filter {
     filter: BitVec,
+    /// Number of frame row edits already replayed onto `filter`, shared with
+    /// the frame so that it knows which edits are still needed.
+    synced: Rc<Cell<usize>>,
+    conditions: Vec<(usize, Predicate)>,
+    next_id: usize,
+    /// Matching rows in display order, so that pages are read without
+    /// scanning the mask.
+    rows: Vec<usize>,
+    /// Frame generation `rows` was built for.
+    indexed: Option<usize>,
+    /// Explicit set of rows the filter started from, conditions only narrow it
+    /// and rows streamed in later never join it.
+    selection: Option<BitVec>,
+    /// Key the matching rows are listed by instead of the display order of the
+    /// frame, such as the rank of the top rows.
+    order: Option<SortKey>,
 }
 
 impl Filter {
-    pub fn add_equalto_filter(&mut self, frame: &Frame, bytes: &[u8], column: &str) {
-        let col = frame.find_by_name(column);
-        let other = single_buffer_into_col_trait(bytes, col.dtype());
-        let mask = col.equal_to(other.as_ref()).unwrap();
+    /// Filter matching exactly the storage positions `rows`.
+    pub fn from_rows(frame: &Frame, rows: &[usize]) -> Self {
+        let mut selection = BitVec::repeat(false, frame.height());
+        rows.iter().for_each(|&row| selection.set(row, true));
+        let ret = Self {
+            filter: selection.clone(),
+            selection: Some(selection),
+            ..Self::default()
+        };
+        frame.sync_reader(&ret.synced);
+        ret
+    }
+
+    /// Lists the matching rows by `key`, ties in storage order.
+    pub fn ordered_by(mut self, key: SortKey) -> Self {
+        self.order = Some(key);
+        self.indexed = None;
+        self
+    }
+
+    pub fn add_equalto_filter(
+        &mut self,
+        frame: &Frame,
+        bytes: &[u8],
+        column: &str,
+    ) -> Result<usize, &'static str> {
+        let condition = Condition {
+            column: column.into(),
+            test: Test::Equal(String::from_utf8_lossy(bytes).into()),
+        };
+        self.add_condition(frame, condition.into())
+    }
 
+    pub fn add_compare_filter(
+        &mut self,
+        frame: &Frame,
+        bytes: &[u8],
+        column: &str,
+        op: CompareOp,
+    ) -> Result<usize, &'static str> {
+        let condition = Condition {
+            column: column.into(),
+            test: Test::Compare(op, String::from_utf8_lossy(bytes).into()),
+        };
+        self.add_condition(frame, condition.into())
+    }
+
+    /// Narrows the filter to the rows also matching `predicate`, returns the id
+    /// to remove it with.
+    pub fn add_condition(
+        &mut self,
+        frame: &Frame,
+        predicate: Predicate,
+    ) -> Result<usize, &'static str> {
+        let mask = predicate.evaluate(frame)?;
+        match self.conditions.is_empty() && self.selection.is_none() {
+            true => self.filter = mask,
+            false => {
+                self.mask(frame);
+                self.filter &= mask;
+            }
+        }
+        frame.sync_reader(&self.synced);
+        self.indexed = None;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.conditions.push((id, predicate));
+        Ok(id)
+    }
+
+    /// Drops the condition `id` and rebuilds the mask from the remaining ones,
+    /// `false` when there is no such condition.
+    pub fn remove_condition(&mut self, frame: &Frame, id: usize) -> Result<bool, &'static str> {
+        let Some(position) = self.conditions.iter().position(|(other, _)| *other == id) else {
+            return Ok(false);
+        };
+        self.conditions.remove(position);
+
+        self.replay_edits(frame);
+        let mut mask = match &self.selection {
+            Some(selection) => {
+                let mut mask = selection.clone();
+                mask.resize(frame.height(), false);
+                mask
+            }
+            None => BitVec::repeat(true, frame.height()),
+        };
+        for (_, predicate) in self.conditions.iter() {
+            mask &= predicate.evaluate(frame)?;
+        }
         self.filter = mask;
+        frame.sync_reader(&self.synced);
+        self.indexed = None;
+        Ok(true)
+    }
+
+    pub fn conditions(&self) -> impl Iterator<Item = &Predicate> {
+        self.conditions.iter().map(|(_, predicate)| predicate)
     }
 
-    fn get(&self) -> &BitSlice {
+    /// Row mask realigned with the rows inserted or deleted since it was built,
+    /// and extended over the rows streamed in since. New rows stay unmatched
+    /// when a condition no longer applies, such as after its column was dropped.
+    pub fn mask(&mut self, frame: &Frame) -> &BitSlice {
+        self.replay_edits(frame);
+
+        let offset = self.filter.len();
+        if offset < frame.height() && !self.conditions.is_empty() {
+            let mut tail = BitVec::repeat(self.selection.is_none(), frame.height() - offset);
+            for (_, predicate) in self.conditions.iter() {
+                match predicate.evaluate_from(frame, offset) {
+                    Ok(mask) => tail &= mask,
+                    Err(_) => tail.fill(false),
+                }
+            }
+            self.filter.extend_from_bitslice(&tail);
+        }
+
         self.filter.as_bitslice()
     }
 
-    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Vec<JsString> {
-        let mask = self.get();
-        frame
-            .columns
-            .iter()
-            .map(|col| col.filter_join(mask, offset, size))
-            .map(|s| JsString::from(s.as_str()))
+    /// Realigns the mask and the selection with the rows inserted or deleted
+    /// since they were last synced, and follows the columns renamed since.
+    /// Inserted rows are matched against the conditions like the rows streamed
+    /// in, an explicit selection never takes them in.
+    fn replay_edits(&mut self, frame: &Frame) {
+        let edits = frame.row_edits_since(self.synced.get());
+        let mut inserted = BitVec::repeat(false, self.filter.len());
+        edits.iter().for_each(|edit| {
+            if let RowEdit::Rename { old, new } = edit {
+                self.rename_column(old, new);
+            }
+            edit.apply_to_mask(&mut self.filter, false);
+            edit.apply_to_mask(&mut inserted, true);
+            if let Some(selection) = self.selection.as_mut() {
+                edit.apply_to_mask(selection, false);
+            }
+        });
+        frame.sync_reader(&self.synced);
+
+        if self.conditions.is_empty() || self.selection.is_some() || inserted.not_any() {
+            return;
+        }
+        let mut matched = BitVec::repeat(true, frame.height());
+        for (_, predicate) in self.conditions.iter() {
+            match predicate.evaluate(frame) {
+                Ok(mask) => matched &= mask,
+                Err(_) => matched.fill(false),
+            }
+        }
+        for row in inserted.iter_ones() {
+            self.filter.set(row, matched[row]);
+        }
+    }
+
+    fn rename_column(&mut self, old: &str, new: &str) {
+        self.conditions
+            .iter_mut()
+            .for_each(|(_, predicate)| predicate.rename_column(old, new));
+        if let Some(key) = self.order.as_mut().filter(|key| key.column == old) {
+            key.column = new.into();
+        }
+    }
+
+    /// Storage positions of the matching rows in display order, or by the
+    /// filter's own key, rebuilt only after the frame changed.
+    pub fn rows(&mut self, frame: &Frame) -> &[usize] {
+        self.mask(frame);
+        if self.indexed != Some(frame.generation()) {
+            self.rows = self
+                .ordered_rows(frame)
+                .unwrap_or_else(|| frame.view_rows(Some(&self.filter), 0, usize::MAX));
+            self.indexed = Some(frame.generation());
+        }
+        &self.rows
+    }
+
+    /// Matching rows sorted by `order`, `None` without a key or once its
+    /// column is gone.
+    fn ordered_rows(&self, frame: &Frame) -> Option<Vec<usize>> {
+        let key = self.order.as_ref()?;
+        let position = frame.column_index(&key.column).ok()?;
+        let rows = self.filter.iter_ones().collect::<Vec<_>>();
+        let values = frame.columns[position].take(&rows);
+
+        let mut ranked = rows.into_iter().zip(values).collect::<Vec<_>>();
+        ranked.sort_by(|(_, left), (_, right)| key.compare(left, right));
+        Some(ranked.into_iter().map(|(row, _)| row).collect())
+    }
+
+    /// Number of rows matched so far.
+    pub fn matched_count(&mut self, frame: &Frame) -> usize {
+        self.rows(frame).len()
+    }
+
+    /// Matching rows from `offset`, in the display order of `frame`.
+    pub fn slice<'a>(
+        &mut self,
+        frame: &'a Frame,
+        offset: usize,
+        size: usize,
+    ) -> Vec<Vec<AnyValue<'a>>> {
+        let rows = self.rows(frame);
+        let start = offset.min(rows.len());
+        let rows = &rows[start..rows.len().min(start.saturating_add(size))];
+        frame.columns.iter().map(|col| col.take(rows)).collect()
+    }
+
+    pub fn join(&mut self, frame: &Frame, offset: usize, size: usize) -> Vec<String> {
+        self.slice(frame, offset, size)
+            .into_iter()
+            .map(join_values)
             .collect()
     }
 }
@@ -0,0 +1,441 @@
+use std::collections::VecDeque;
+
+use bitvec::prelude::BitVec;
+
+use crate::{
+    column::Column,
+    rows::RowEdit,
+    series::{errors::EditError, value::AnyValue, SeriesEnum, SeriesTrait},
+    Frame, Words,
+};
+
+const DEFAULT_LIMIT: usize = 100;
+
+/// Operation that could not be applied, handed back with the reason.
+pub type Rejected = (Box<Operation>, EditError);
+
+/// Mutation of a frame. Applying one hands back the operation undoing it.
+pub enum Operation {
+    SetCell {
+        row: usize,
+        column: usize,
+        text: String,
+    },
+    /// Cell put back to the exact value it held, nulls included.
+    RestoreCell {
+        row: usize,
+        column: usize,
+        value: AnyValue<'static>,
+    },
+    /// Rows of every column slotted in at the positions set in `mask`.
+    InsertRows {
+        mask: BitVec,
+        rows: Vec<SeriesEnum>,
+    },
+    DeleteRows {
+        mask: BitVec,
+    },
+    InsertColumn {
+        position: usize,
+        column: Column,
+    },
+    RemoveColumn {
+        position: usize,
+    },
+    ReplaceColumn {
+        position: usize,
+        column: Column,
+    },
+    Rename {
+        position: usize,
+        name: String,
+    },
+    /// Column `i` afterwards is the column found at `order[i]` beforehand.
+    Reorder {
+        order: Vec<usize>,
+    },
+}
+
+impl Operation {
+    /// Applies the operation and returns its inverse. An operation that cannot
+    /// be applied leaves the frame untouched and is handed back with the error.
+    pub fn apply(self, frame: &mut Frame) -> Result<Operation, Rejected> {
+        let inverse = match self {
+            Operation::SetCell { row, column, text } => {
+                let old = match frame.columns.get_mut(column) {
+                    Some(target) => target.set(row, text.as_bytes()),
+                    None => Err(EditError::UnknownColumn),
+                };
+                match old {
+                    Ok(value) => Operation::RestoreCell { row, column, value },
+                    Err(error) => {
+                        let operation = Operation::SetCell { row, column, text };
+                        return Err((Box::new(operation), error));
+                    }
+                }
+            }
+            Operation::RestoreCell { row, column, value } => {
+                let old = match frame.columns.get_mut(column) {
+                    Some(target) => target.set_value(row, &value),
+                    None => Err(EditError::UnknownColumn),
+                };
+                match old {
+                    Ok(old) => Operation::RestoreCell {
+                        row,
+                        column,
+                        value: old,
+                    },
+                    Err(error) => {
+                        let operation = Operation::RestoreCell { row, column, value };
+                        return Err((Box::new(operation), error));
+                    }
+                }
+            }
+            Operation::InsertRows { mask, rows } => {
+                if let Err(rows) = frame.restore_rows(&mask, rows) {
+                    let operation = Operation::InsertRows { mask, rows };
+                    return Err((Box::new(operation), EditError::InvalidValue));
+                }
+                Operation::DeleteRows { mask }
+            }
+            Operation::DeleteRows { mask } => {
+                let rows = frame.remove_rows(&mask);
+                Operation::InsertRows { mask, rows }
+            }
+            Operation::InsertColumn { position, column } => {
+                if position > frame.columns.len() {
+                    let operation = Operation::InsertColumn { position, column };
+                    return Err((Box::new(operation), EditError::OutOfBounds));
+                }
+                let column = fill_height(frame, column);
+                frame.columns.insert(position, column);
+                Operation::RemoveColumn { position }
+            }
+            Operation::RemoveColumn { position } => {
+                if position >= frame.columns.len() {
+                    let operation = Operation::RemoveColumn { position };
+                    return Err((Box::new(operation), EditError::UnknownColumn));
+                }
+                let column = frame.columns.remove(position);
+                Operation::InsertColumn { position, column }
+            }
+            Operation::ReplaceColumn { position, column } => {
+                if position >= frame.columns.len() {
+                    let operation = Operation::ReplaceColumn { position, column };
+                    return Err((Box::new(operation), EditError::UnknownColumn));
+                }
+                let column = fill_height(frame, column);
+                let column = std::mem::replace(&mut frame.columns[position], column);
+                Operation::ReplaceColumn { position, column }
+            }
+            Operation::Rename { position, name } => {
+                let Some(target) = frame.columns.get_mut(position) else {
+                    let operation = Operation::Rename { position, name };
+                    return Err((Box::new(operation), EditError::UnknownColumn));
+                };
+                let old = target.set_name(name.clone());
+                frame
+                    .sort_keys
+                    .iter_mut()
+                    .filter(|key| key.column == old)
+                    .for_each(|key| key.column = name.clone());
+                frame.record_row_edit(RowEdit::Rename {
+                    old: old.clone(),
+                    new: name,
+                });
+                Operation::Rename {
+                    position,
+                    name: old,
+                }
+            }
+            Operation::Reorder { order } => {
+                let mut columns = std::mem::take(&mut frame.columns)
+                    .into_iter()
+                    .map(Some)
+                    .collect::<Vec<_>>();
+                frame.columns = order
+                    .iter()
+                    .filter_map(|&position| columns[position].take())
+                    .collect();
+
+                let mut inverse = vec![0; order.len()];
+                order
+                    .iter()
+                    .enumerate()
+                    .for_each(|(new, &old)| inverse[old] = new);
+                Operation::Reorder { order: inverse }
+            }
+        };
+
+        Ok(inverse)
+    }
+
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Operation::SetCell { text, .. } => text.capacity(),
+            Operation::RestoreCell { value, .. } => match value {
+                AnyValue::Str(text) => text.len(),
+                _ => 0,
+            },
+            Operation::InsertRows { mask, rows } => {
+                mask.capacity() / 8
+                    + rows
+                        .iter()
+                        .map(|rows| rows.memory_usage().total())
+                        .sum::<usize>()
+            }
+            Operation::DeleteRows { mask } => mask.capacity() / 8,
+            Operation::InsertColumn { column, .. } | Operation::ReplaceColumn { column, .. } => {
+                column.memory_usage().total()
+            }
+            Operation::RemoveColumn { .. } => 0,
+            Operation::Rename { name, .. } => name.capacity(),
+            Operation::Reorder { order } => order.capacity() * std::mem::size_of::<usize>(),
+        }
+    }
+}
+
+/// Pads a stored `column` with blank cells for the rows streamed in since it
+/// was taken out of the frame.
+fn fill_height(frame: &Frame, mut column: Column) -> Column {
+    let height = frame.height();
+    if !frame.columns.is_empty() && column.len() < height {
+        column.extend_from_words(Words::blank(height - column.len()));
+    }
+    column
+}
+
+/// Bounded undo stack plus the redo stack it feeds.
+pub struct History {
+    undo: VecDeque<Operation>,
+    redo: Vec<Operation>,
+    limit: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+}
+
+impl History {
+    /// Stores the inverse of a fresh mutation, forgetting whatever could be redone.
+    fn record(&mut self, inverse: Operation) {
+        self.redo.clear();
+        self.push_undo(inverse);
+    }
+
+    fn push_undo(&mut self, inverse: Operation) {
+        self.undo.push_back(inverse);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.undo.len() > limit {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn heap_size(&self) -> usize {
+        self.undo
+            .iter()
+            .chain(self.redo.iter())
+            .map(Operation::heap_size)
+            .sum()
+    }
+}
+
+impl Frame {
+    /// Applies `operation` and records its inverse.
+    pub fn execute(&mut self, operation: Operation) -> Result<(), EditError> {
+        let inverse = operation.apply(self).map_err(|(_, error)| error)?;
+        self.reorder_after(&inverse);
+        self.history.record(inverse);
+        self.track_memory();
+        Ok(())
+    }
+
+    /// Reverts the latest mutation, `false` when there is nothing to undo. A
+    /// failed undo stays on the stack.
+    pub fn undo(&mut self) -> Result<bool, EditError> {
+        let Some(operation) = self.history.undo.pop_back() else {
+            return Ok(false);
+        };
+        let inverse = match operation.apply(self) {
+            Ok(inverse) => inverse,
+            Err((operation, error)) => {
+                self.history.undo.push_back(*operation);
+                return Err(error);
+            }
+        };
+        self.reorder_after(&inverse);
+        self.history.redo.push(inverse);
+        self.track_memory();
+        Ok(true)
+    }
+
+    pub fn redo(&mut self) -> Result<bool, EditError> {
+        let Some(operation) = self.history.redo.pop() else {
+            return Ok(false);
+        };
+        let inverse = match operation.apply(self) {
+            Ok(inverse) => inverse,
+            Err((operation, error)) => {
+                self.history.redo.push(*operation);
+                return Err(error);
+            }
+        };
+        self.reorder_after(&inverse);
+        self.history.push_undo(inverse);
+        self.track_memory();
+        Ok(true)
+    }
+
+    /// Brings the row order up to date after an edit, a single changed cell
+    /// is moved on its own instead of sorting every row again.
+    fn reorder_after(&mut self, inverse: &Operation) {
+        match *inverse {
+            Operation::RestoreCell { row, .. } => self.reposition(row),
+            _ => self.resort(),
+        }
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Operation;
+    use crate::{
+        series::{errors::EditError, value::AnyValue},
+        type_parser::Codes,
+        ChunkFromJsBytes, Frame,
+    };
+
+    #[test]
+    fn undo_redo() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30\nJolteon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+        let original = frame.find_by_name("Name").join(0, 3);
+
+        frame.set_cell(0, "Level", "26").unwrap();
+        frame.delete_rows(&[1]).unwrap();
+        frame.insert_rows(0, b"Eevee,5").unwrap();
+        frame.cast_column("Level", Codes::Float64).unwrap();
+        frame.rename_column("Name", "Pokemon").unwrap();
+        frame.drop_column("Level").unwrap();
+        assert_eq!(frame.width(), 1);
+
+        while frame.undo().unwrap() {}
+        assert!(!frame.history().can_undo());
+        assert_eq!(frame.width(), 2);
+        assert_eq!(frame.find_by_name("Name").join(0, 3), original);
+        assert_eq!(frame.find_by_name("Level").dtype(), Codes::Int32);
+        assert_eq!(frame.find_by_name("Level").get(0), Some(AnyValue::I32(25)));
+
+        assert!(frame.redo().unwrap());
+        assert!(frame.redo().unwrap());
+        assert_eq!(frame.height(), 2);
+        frame.set_cell(1, "Level", "31").unwrap();
+        assert!(!frame.history().can_redo());
+
+        frame.history_mut().set_limit(1);
+        assert!(frame.undo().unwrap());
+        assert!(!frame.undo().unwrap());
+        assert_eq!(
+            frame.find_by_name("Level").slice(0, 2),
+            vec![AnyValue::I32(26), AnyValue::I32(30)]
+        );
+    }
+
+    #[test]
+    fn undo_after_streaming() {
+        let mut frame = Frame::new();
+        frame.append(
+            b"Name,Level,Weight\nFlareon,25,25.0\nVaporeon,30,29.0\n",
+            true,
+        );
+        frame.drop_column("Level").unwrap();
+        frame.cast_column("Weight", Codes::Int32).unwrap();
+        frame.append(b"Jolteon,30,24.5\nEevee,5,6.5", false);
+        frame.append_remainder();
+
+        // columns kept in the history get blank cells for the streamed rows
+        while frame.undo().unwrap() {}
+        assert_eq!(
+            frame
+                .columns
+                .iter()
+                .map(|column| column.len())
+                .collect::<Vec<_>>(),
+            vec![4, 4, 4]
+        );
+        assert_eq!(
+            frame.slice_values(3, 1),
+            vec![
+                vec![AnyValue::Str("Eevee".into())],
+                vec![AnyValue::Null],
+                vec![AnyValue::Null]
+            ]
+        );
+        assert_eq!(frame.find_by_name("Weight").dtype(), Codes::Float32);
+        assert!(frame.redo().unwrap());
+        assert_eq!(frame.width(), 2);
+        assert!(frame.undo().unwrap());
+        assert_eq!(frame.find_by_name("Level").len(), 4);
+    }
+
+    #[test]
+    fn restore_exact_values() {
+        // the second name is not valid utf8, so it is read as null
+        let bytes = b"Name,Level\nFlareon,25\n\xff,";
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        frame.set_cell(1, "Name", "Vaporeon").unwrap();
+        frame.set_cell(1, "Level", "30").unwrap();
+        while frame.undo().unwrap() {}
+        assert_eq!(
+            frame.slice_values(1, 1),
+            vec![vec![AnyValue::Null], vec![AnyValue::Null]]
+        );
+        assert!(frame.redo().unwrap());
+        assert_eq!(
+            frame.find_by_name("Name").get(1),
+            Some(AnyValue::Str("Vaporeon".into()))
+        );
+
+        // an operation that fails is kept for another try
+        frame
+            .history
+            .undo
+            .push_back(Operation::RemoveColumn { position: 5 });
+        assert_eq!(frame.undo(), Err(EditError::UnknownColumn));
+        assert_eq!(frame.history().undo.len(), 2);
+        assert_eq!(frame.width(), 2);
+    }
+}
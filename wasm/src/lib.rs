@@ -1,18 +1,39 @@
 #![feature(iter_intersperse)]
 #![feature(option_get_or_insert_default)]
+pub mod aggregate;
 pub mod column;
 pub mod command;
 pub mod csv_parser;
 pub mod filter;
+pub mod history;
+pub mod memory;
+pub mod predicate;
 pub mod public;
+pub mod rows;
+pub mod schema;
 pub mod series;
+pub mod sort;
+pub mod stats;
 pub mod type_parser;
 pub mod utils;
 
-use column::{Column, SeriesEnum};
+use column::Column;
 use console_error_panic_hook::hook;
 use csv_parser::LineSplitter;
-use std::panic;
+use history::{History, Operation};
+use memory::MemoryUsage;
+use rows::RowEdit;
+use series::{
+    errors::EditError,
+    value::{join_values, AnyValue},
+    SeriesEnum,
+};
+use sort::SortKey;
+use std::{
+    cell::{Cell, RefCell},
+    panic,
+    rc::Weak,
+};
 use type_parser::*;
 use utils::{HeaderFillerGenerator, LendingIterator};
 use wasm_bindgen::prelude::wasm_bindgen;
@@ -24,6 +45,14 @@ pub struct Words {
 }
 
 impl Words {
+    /// `count` empty words.
+    pub fn blank(count: usize) -> Self {
+        Self {
+            buff: Vec::new(),
+            offsets: vec![0; count],
+        }
+    }
+
     pub fn last(&self) -> Option<usize> {
         self.offsets.last().copied()
     }
@@ -37,10 +66,49 @@ impl Words {
         }
     }
 
+    pub fn get(&self, index: usize) -> Option<&[u8]> {
+        let end = *self.offsets.get(index)?;
+        let start = index.checked_sub(1).map_or(0, |i| self.offsets[i]);
+        Some(&self.buff[start..end])
+    }
+
+    pub fn replace(&mut self, index: usize, data: &[u8]) {
+        let end = self.offsets[index];
+        let start = index.checked_sub(1).map_or(0, |i| self.offsets[i]);
+        self.buff.splice(start..end, data.iter().copied());
+        self.offsets[index..].iter_mut().for_each(|offset| {
+            *offset = *offset + data.len() - (end - start);
+        });
+    }
+
+    pub fn append(&mut self, other: Words) {
+        let shift = self.last().unwrap_or_default();
+        self.buff.extend_from_slice(&other.buff);
+        self.offsets
+            .extend(other.offsets.into_iter().map(|offset| offset + shift));
+    }
+
+    /// Moves the words from `at` onwards into a new buffer.
+    pub fn split_off(&mut self, at: usize) -> Words {
+        let start = at.checked_sub(1).map_or(0, |i| self.offsets[i]);
+        let buff = self.buff.split_off(start);
+        let offsets = self
+            .offsets
+            .split_off(at)
+            .into_iter()
+            .map(|offset| offset - start)
+            .collect();
+        Words { buff, offsets }
+    }
+
     pub fn len(&self) -> usize {
         self.offsets.len()
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.buff.capacity() + self.offsets.capacity() * std::mem::size_of::<usize>()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.offsets.is_empty()
     }
@@ -286,112 +354,205 @@ impl ChunkBuilder {
 
 #[wasm_bindgen]
 pub struct Frame {
+    id: usize,
+    /// Display order of the rows while a sort is active, empty otherwise.
     index: Vec<usize>,
+    sort_keys: Vec<SortKey>,
+    /// Bumped by `resort` whenever the rows or their order may have changed.
+    generation: usize,
     columns: Vec<Column>,
     n_chunks: usize,
+    /// Number of fields in each line of the streamed csv.
+    n_fields: usize,
     remainder: Vec<u8>,
+    row_edits: Vec<RowEdit>,
+    /// Number of row edits dropped from the front of `row_edits` once every
+    /// reader had replayed them.
+    dropped_edits: usize,
+    /// Number of row edits each live filter has replayed.
+    edit_readers: RefCell<Vec<Weak<Cell<usize>>>>,
+    history: History,
 }
 
 #[allow(clippy::new_without_default)]
 impl Frame {
     fn new() -> Self {
         Self {
+            id: memory::next_frame_id(),
             index: Vec::new(),
+            sort_keys: Vec::new(),
+            generation: 0,
             columns: Vec::new(),
             n_chunks: 0,
+            n_fields: 0,
             remainder: Vec::new(),
+            row_edits: Vec::new(),
+            dropped_edits: 0,
+            edit_readers: RefCell::default(),
+            history: History::default(),
         }
     }
 
+    /// Frame holding already built columns, such as the result of a group by.
+    fn from_columns(columns: Vec<Column>) -> Self {
+        let mut ret = Self::new();
+        ret.columns = columns;
+        ret.track_memory();
+        ret
+    }
+
     fn new_from_entry(&mut self, mut entry: ChunkFromJsBytes) {
         let header = entry.fill_header();
+        self.n_fields = entry.buffers.len();
 
         self.columns = entry
             .iter_with_code()
             .zip(header.into_iter())
-            .map(|((code, words), name_bytes)| match code {
+            .enumerate()
+            .map(|(field, ((code, words), name_bytes))| match code {
                 code @ Codes::Boolean => {
                     let parsed = parse_bool(words);
-                    let series = SeriesEnum::Bool(Box::new(parsed));
+                    let series = SeriesEnum::Bool(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Int32 => {
                     let parsed = parse_type::<i32>(words);
-                    let series = SeriesEnum::I32(Box::new(parsed));
+                    let series = SeriesEnum::I32(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Int64 => {
                     let parsed = parse_type::<i64>(words);
-                    let series = SeriesEnum::I64(Box::new(parsed));
+                    let series = SeriesEnum::I64(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Int128 => {
                     let parsed = parse_type::<i128>(words);
-                    let series = SeriesEnum::I128(Box::new(parsed));
+                    let series = SeriesEnum::I128(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Float32 => {
                     let parsed = parse_type::<f32>(words);
-                    let series = SeriesEnum::F32(Box::new(parsed));
+                    let series = SeriesEnum::F32(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Float64 => {
                     let parsed = parse_type::<f64>(words);
-                    let series = SeriesEnum::F64(Box::new(parsed));
+                    let series = SeriesEnum::F64(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 code @ Codes::Any => {
                     let parsed = parse_utf8(words);
-                    let series = SeriesEnum::Any(Box::new(parsed));
+                    let series = SeriesEnum::Any(parsed.into());
                     let name = String::from_utf8(name_bytes.to_vec()).unwrap();
-                    Column::new(series, name, code)
+                    Column::new(series, name, code).with_source(field)
                 }
                 _ => unreachable!(),
             })
             .collect();
-
-        if let Some(v) = self.columns.get(0) {
-            self.index = (0..v.len()).collect();
-        }
     }
 
+    /// Hands each column the buffer of its csv field, columns added since the
+    /// stream started get blank cells.
     fn extend_from_buffers(&mut self, buffers: Vec<Words>) {
-        self.columns
-            .iter_mut()
-            .zip(buffers.into_iter())
-            .for_each(|(col, buff)| col.extend_from_words(buff));
+        let count = buffers.first().map_or(0, Words::len);
+        let mut buffers = buffers.into_iter().map(Some).collect::<Vec<_>>();
+        self.columns.iter_mut().for_each(|col| {
+            let words = col
+                .source()
+                .and_then(|field| buffers.get_mut(field)?.take())
+                .unwrap_or_else(|| Words::blank(count));
+            col.extend_from_words(words)
+        });
     }
 
     pub fn append(&mut self, bytes: &[u8], skip_header: bool) {
         panic::set_hook(Box::new(hook));
 
+        let height = self.height();
         let old_rem = (!self.remainder.is_empty()).then(|| self.remainder.to_owned());
         let chunk = ChunkFromJsBytes::from_bytes(bytes)
             .with_missing_bytes(old_rem)
             .with_header(skip_header && self.n_chunks == 0)
-            .with_column_number(self.columns.len())
+            .with_column_number(self.n_fields)
             .read()
             .pull_last_line();
 
         self.remainder = chunk.remainder.clone().unwrap_or_default();
-        if self.columns.is_empty() {
+        if self.n_fields == 0 {
             self.new_from_entry(chunk);
         } else {
             self.extend_from_buffers(chunk.buffers);
         };
 
         self.n_chunks += 1;
+        self.sort_appended(height);
+        self.track_memory();
     }
 
     pub fn append_remainder(&mut self) {
-        let chunk = ChunkFromJsBytes::single_line(&self.remainder, self.columns.len());
+        let height = self.height();
+        let chunk = ChunkFromJsBytes::single_line(&self.remainder, self.n_fields);
         self.extend_from_buffers(chunk.buffers);
+        self.sort_appended(height);
+        self.track_memory();
+    }
+
+    /// Rows of every column from `offset`, in display order.
+    pub fn slice_values(&self, offset: usize, size: usize) -> Vec<Vec<AnyValue<'_>>> {
+        if self.index.is_empty() {
+            return self
+                .columns
+                .iter()
+                .map(|column| column.slice(offset, size))
+                .collect();
+        }
+
+        let rows = self.view_rows(None, offset, size);
+        self.columns
+            .iter()
+            .map(|column| column.take(&rows))
+            .collect()
+    }
+
+    pub fn slice_joined_values(&self, offset: usize, size: usize) -> Vec<String> {
+        self.slice_values(offset, size)
+            .into_iter()
+            .map(join_values)
+            .collect()
+    }
+
+    /// Writes `text` into the cell displayed at `row` after validating it against
+    /// the column type and returns the cell as it now renders.
+    pub fn set_cell(&mut self, row: usize, column: &str, text: &str) -> Result<String, EditError> {
+        let position = self.column_index(column)?;
+        let row = self.physical_row(row);
+        self.execute(Operation::SetCell {
+            row,
+            column: position,
+            text: text.into(),
+        })?;
+
+        let rendered = self.columns[position].get(row).unwrap_or(AnyValue::Null);
+        Ok(rendered.to_string())
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let columns: MemoryUsage = self.columns.iter().map(Column::memory_usage).sum();
+        columns
+            + MemoryUsage::remainder(self.remainder.capacity())
+            + MemoryUsage::index(self.index.capacity() * std::mem::size_of::<usize>())
+            + MemoryUsage::index(self.row_edits.iter().map(RowEdit::heap_size).sum())
+            + MemoryUsage::history(self.history.heap_size())
+    }
+
+    fn track_memory(&self) {
+        memory::track(self.id, self.memory_usage().total());
     }
 
     pub fn find_by_name(&self, name: &str) -> &Column {
@@ -399,6 +560,12 @@ impl Frame {
     }
 }
 
+impl Drop for Frame {
+    fn drop(&mut self) {
+        memory::release(self.id);
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -425,6 +592,29 @@ mod test {
         assert_eq!(parsed.len(), 2);
     }
 
+    #[test]
+    fn append_words() {
+        let mut left = Words::default();
+        left.extend(b"Flareon");
+        left.extend(b"");
+
+        let mut right = Words::default();
+        right.extend(b"Jolteon");
+        left.append(right);
+
+        assert_eq!(left.len(), 3);
+        assert_eq!(left.get(0), Some("Flareon".as_bytes()));
+        assert_eq!(left.get(1), Some("".as_bytes()));
+        assert_eq!(left.get(2), Some("Jolteon".as_bytes()));
+        assert_eq!(left.get(3), None);
+
+        left.replace(1, b"Umbreon");
+        left.replace(0, b"Eevee");
+        assert_eq!(left.get(0), Some("Eevee".as_bytes()));
+        assert_eq!(left.get(1), Some("Umbreon".as_bytes()));
+        assert_eq!(left.get(2), Some("Jolteon".as_bytes()));
+    }
+
     #[test]
     fn bytes_into_chunk() {
         let bytes = "Flareon,Jolteon,Vaporeon\nEsp".as_bytes();
@@ -451,5 +641,41 @@ mod test {
 
         frame.append_remainder();
         assert_eq!(frame.height(), 3);
+
+        frame.rechunk();
+        assert_eq!(frame.height(), 3);
+
+        let usage = frame.memory_usage();
+        assert!(usage.strings_bytes() > 0);
+        assert_eq!(memory::total(), usage.total());
+        assert_eq!(
+            frame.find_by_name("FieldOne").join(1, 2),
+            "VaporeonDELIMITER_TOKEN"
+        );
+    }
+
+    #[test]
+    fn set_cell() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        assert_eq!(
+            frame.set_cell(1, "Level", "3a"),
+            Err(EditError::InvalidValue)
+        );
+        assert_eq!(frame.set_cell(1, "Level", "36"), Ok("36".into()));
+        assert_eq!(frame.set_cell(0, "Level", ""), Ok("".into()));
+        assert_eq!(frame.set_cell(2, "Level", "1"), Err(EditError::OutOfBounds));
+        assert_eq!(frame.set_cell(0, "Name", "Leafeon"), Ok("Leafeon".into()));
+
+        let stats = frame.find_by_name("Level").stats();
+        assert_eq!(stats.null_count(), 1);
+        assert_eq!(stats.min(), Some(&AnyValue::I32(36)));
+        assert_eq!(
+            frame.find_by_name("Name").join(0, 2),
+            "LeafeonDELIMITER_TOKENVaporeon"
+        );
     }
 }
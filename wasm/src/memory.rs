@@ -0,0 +1,168 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    iter::Sum,
+    ops::Add,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use wasm_bindgen::prelude::*;
+
+static NEXT_FRAME_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static LIVE_FRAMES: RefCell<HashMap<usize, usize>> = RefCell::new(HashMap::new());
+}
+
+/// Heap bytes held by a column or a frame, split by buffer kind.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    values: usize,
+    validity: usize,
+    strings: usize,
+    remainder: usize,
+    index: usize,
+    history: usize,
+}
+
+impl MemoryUsage {
+    pub fn values(bytes: usize) -> Self {
+        Self {
+            values: bytes,
+            ..Self::default()
+        }
+    }
+
+    pub fn validity(bits: usize) -> Self {
+        Self {
+            validity: bits.div_ceil(8),
+            ..Self::default()
+        }
+    }
+
+    pub fn strings(bytes: usize) -> Self {
+        Self {
+            strings: bytes,
+            ..Self::default()
+        }
+    }
+
+    pub fn remainder(bytes: usize) -> Self {
+        Self {
+            remainder: bytes,
+            ..Self::default()
+        }
+    }
+
+    pub fn index(bytes: usize) -> Self {
+        Self {
+            index: bytes,
+            ..Self::default()
+        }
+    }
+
+    /// Data kept alive by the undo and redo stacks.
+    pub fn history(bytes: usize) -> Self {
+        Self {
+            history: bytes,
+            ..Self::default()
+        }
+    }
+}
+
+impl Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            values: self.values + other.values,
+            validity: self.validity + other.validity,
+            strings: self.strings + other.strings,
+            remainder: self.remainder + other.remainder,
+            index: self.index + other.index,
+            history: self.history + other.history,
+        }
+    }
+}
+
+impl Sum for MemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+#[wasm_bindgen]
+impl MemoryUsage {
+    #[wasm_bindgen(getter = values)]
+    pub fn values_bytes(&self) -> usize {
+        self.values
+    }
+
+    #[wasm_bindgen(getter = validity)]
+    pub fn validity_bytes(&self) -> usize {
+        self.validity
+    }
+
+    #[wasm_bindgen(getter = strings)]
+    pub fn strings_bytes(&self) -> usize {
+        self.strings
+    }
+
+    #[wasm_bindgen(getter = remainder)]
+    pub fn remainder_bytes(&self) -> usize {
+        self.remainder
+    }
+
+    #[wasm_bindgen(getter = index)]
+    pub fn index_bytes(&self) -> usize {
+        self.index
+    }
+
+    #[wasm_bindgen(getter = history)]
+    pub fn history_bytes(&self) -> usize {
+        self.history
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn total(&self) -> usize {
+        self.values + self.validity + self.strings + self.remainder + self.index + self.history
+    }
+}
+
+pub fn next_frame_id() -> usize {
+    NEXT_FRAME_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Records the latest footprint of a live frame.
+pub fn track(frame_id: usize, bytes: usize) {
+    LIVE_FRAMES.with(|frames| frames.borrow_mut().insert(frame_id, bytes));
+}
+
+pub fn release(frame_id: usize) {
+    LIVE_FRAMES.with(|frames| frames.borrow_mut().remove(&frame_id));
+}
+
+/// Bytes held by every frame that has not been freed yet.
+pub fn total() -> usize {
+    LIVE_FRAMES.with(|frames| frames.borrow().values().sum())
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryUsage;
+
+    #[test]
+    fn add_usage() {
+        let usage = [
+            MemoryUsage::values(64),
+            MemoryUsage::validity(9),
+            MemoryUsage::strings(10),
+        ]
+        .into_iter()
+        .sum::<MemoryUsage>();
+
+        assert_eq!(usage.validity_bytes(), 2);
+        assert_eq!(usage.total(), 76);
+    }
+}
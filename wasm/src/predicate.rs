@@ -0,0 +1,358 @@
+use bitvec::prelude::BitVec;
+use ordered_float::OrderedFloat;
+use regex::{Regex, RegexBuilder};
+
+use crate::{filter::literal_into_series, series::CompareOp, Frame};
+
+/// Search in the cells of a string column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextOp {
+    Contains,
+    StartsWith,
+    EndsWith,
+    /// The pattern is a regular expression.
+    Matches,
+}
+
+impl TextOp {
+    pub fn regex(self, pattern: &str, case_insensitive: bool) -> Result<Regex, regex::Error> {
+        let pattern = match self {
+            TextOp::Contains => regex::escape(pattern),
+            TextOp::StartsWith => format!("^{}", regex::escape(pattern)),
+            TextOp::EndsWith => format!("{}$", regex::escape(pattern)),
+            TextOp::Matches => pattern.into(),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+    }
+}
+
+/// Test applied to every cell of a single column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Test {
+    Equal(String),
+    /// Numeric equality up to `tolerance`.
+    Near {
+        value: String,
+        tolerance: OrderedFloat<f64>,
+    },
+    Compare(CompareOp, String),
+    /// Membership in a list of values.
+    In(Vec<String>),
+    /// Range with both bounds included.
+    Between(String, String),
+    Text {
+        op: TextOp,
+        pattern: String,
+        case_insensitive: bool,
+    },
+    /// Missing cells, or the present ones when `negated`.
+    Null {
+        negated: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Condition {
+    pub column: String,
+    pub test: Test,
+}
+
+impl Condition {
+    pub fn evaluate(&self, frame: &Frame) -> Result<BitVec, &'static str> {
+        self.evaluate_from(frame, 0)
+    }
+
+    /// Mask of the rows from `offset` onwards.
+    pub fn evaluate_from(&self, frame: &Frame, offset: usize) -> Result<BitVec, &'static str> {
+        let position = frame
+            .column_index(&self.column)
+            .map_err(|_| "Unknown column")?;
+        let tail;
+        let column = match offset {
+            0 => &frame.columns[position],
+            offset => {
+                tail = frame.columns[position].tail(offset);
+                &tail
+            }
+        };
+
+        let literal = |value: &String| {
+            literal_into_series(value.as_bytes(), column.dtype())
+                .map_err(|_| "Value does not match the column type")
+        };
+        let mask = match &self.test {
+            Test::Equal(value) => {
+                let other = literal(value)?;
+                column.equal_to(&other)
+            }
+            Test::Near { value, tolerance } => {
+                let other = literal(value)?;
+                column.equal_within(&other, tolerance.0)
+            }
+            Test::Compare(op, value) => {
+                let other = literal(value)?;
+                column.compare(*op, &other)
+            }
+            Test::In(values) => {
+                // each value is read and promoted as it would be by `=`
+                let mut mask = BitVec::repeat(false, column.len());
+                for value in values {
+                    mask |= column
+                        .equal_to(&literal(value)?)
+                        .map_err(|_| "Value does not match the column type")?;
+                }
+                Ok(mask)
+            }
+            Test::Between(low, high) => {
+                let (low, high) = (literal(low)?, literal(high)?);
+                column.between(&low, &high)
+            }
+            Test::Text {
+                op,
+                pattern,
+                case_insensitive,
+            } => {
+                let regex = op
+                    .regex(pattern, *case_insensitive)
+                    .map_err(|_| "Invalid regular expression")?;
+                return column
+                    .matches(&regex)
+                    .map_err(|_| "Text search needs a string column");
+            }
+            Test::Null { negated } => Ok(column.is_null(*negated)),
+        };
+        mask.map_err(|_| "Value does not match the column type")
+    }
+}
+
+/// Boolean combination of conditions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predicate {
+    Condition(Condition),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+impl Predicate {
+    pub fn evaluate(&self, frame: &Frame) -> Result<BitVec, &'static str> {
+        self.evaluate_from(frame, 0)
+    }
+
+    /// Mask of the rows from `offset` onwards, used to extend a mask as rows
+    /// are streamed in.
+    pub fn evaluate_from(&self, frame: &Frame, offset: usize) -> Result<BitVec, &'static str> {
+        match self {
+            Predicate::Condition(condition) => condition.evaluate_from(frame, offset),
+            Predicate::Not(inner) => Ok(!inner.evaluate_from(frame, offset)?),
+            Predicate::And(left, right) => {
+                Ok(left.evaluate_from(frame, offset)? & right.evaluate_from(frame, offset)?)
+            }
+            Predicate::Or(left, right) => {
+                Ok(left.evaluate_from(frame, offset)? | right.evaluate_from(frame, offset)?)
+            }
+        }
+    }
+
+    /// Points the conditions on column `old` at its new name `new`.
+    pub fn rename_column(&mut self, old: &str, new: &str) {
+        match self {
+            Predicate::Condition(condition) => {
+                if condition.column == old {
+                    condition.column = new.into();
+                }
+            }
+            Predicate::Not(inner) => inner.rename_column(old, new),
+            Predicate::And(left, right) | Predicate::Or(left, right) => {
+                left.rename_column(old, new);
+                right.rename_column(old, new);
+            }
+        }
+    }
+}
+
+impl From<Condition> for Predicate {
+    fn from(condition: Condition) -> Self {
+        Predicate::Condition(condition)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::parser::parse_predicate, filter::Filter, sort::SortKey, ChunkFromJsBytes, Frame,
+    };
+
+    #[test]
+    fn conditions() {
+        let bytes = "Name,Type,Level\nFlareon,Fire,25\nVaporeon,Water,30\nJolteon,Electric,30\nMoltres,Fire,50"
+            .as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let (_, predicate) =
+            parse_predicate("(Type = Fire or Type = Water) and not Level > 40").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![true, true, false, false]
+        );
+
+        let (_, predicate) =
+            parse_predicate("Level between 26 and 50 or Name in (Flareon, Eevee)").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![true, true, true, true]
+        );
+        let (_, predicate) =
+            parse_predicate("Type in (Water, Electric) and Level between 30 and 30").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![false, true, true, false]
+        );
+
+        let (_, predicate) = parse_predicate(
+            "Name icontains EON and not Name matches /^(V|J)/ or Type ends with ire",
+        )
+        .unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![true, false, false, true]
+        );
+        let (_, predicate) = parse_predicate("Level contains 3").unwrap();
+        assert_eq!(
+            predicate.evaluate(&frame),
+            Err("Text search needs a string column")
+        );
+        let (_, predicate) = parse_predicate("Name matches /(/").unwrap();
+        assert_eq!(
+            predicate.evaluate(&frame),
+            Err("Invalid regular expression")
+        );
+
+        // literals wider than the column type are promoted, not read as null
+        let (_, predicate) = parse_predicate("Level > 27.5 and Level < 5000000000").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![false, true, true, true]
+        );
+        let (_, predicate) = parse_predicate("Level = 30.0 within 0.5").unwrap();
+        assert_eq!(predicate.evaluate(&frame).unwrap().count_ones(), 2);
+        let (_, predicate) = parse_predicate("Level < true").unwrap();
+        assert_eq!(
+            predicate.evaluate(&frame),
+            Err("Value does not match the column type")
+        );
+
+        let (_, predicate) = parse_predicate("Level is null").unwrap();
+        assert!(predicate.evaluate(&frame).unwrap().not_any());
+        frame.set_cell(1, "Level", "").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter()
+                .by_vals()
+                .collect::<Vec<_>>(),
+            vec![false, true, false, false]
+        );
+        let (_, predicate) = parse_predicate("Level is not null and Name is not null").unwrap();
+        assert_eq!(predicate.evaluate(&frame).unwrap().count_ones(), 3);
+        // a literal that is not a number never stands for the null cell
+        for text in ["Level = abc", "Level = true", "Level in (abc, 25)"] {
+            let (_, predicate) = parse_predicate(text).unwrap();
+            assert_eq!(
+                predicate.evaluate(&frame),
+                Err("Value does not match the column type")
+            );
+        }
+        let (_, predicate) = parse_predicate("Level in (5000000000, 25)").unwrap();
+        assert_eq!(
+            predicate
+                .evaluate(&frame)
+                .unwrap()
+                .iter_ones()
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+        frame.undo().unwrap();
+
+        let mut filter = Filter::default();
+        let (_, predicate) = parse_predicate("Level >= 30").unwrap();
+        let level = filter.add_condition(&frame, predicate).unwrap();
+        let (_, predicate) = parse_predicate("Type != Electric").unwrap();
+        filter.add_condition(&frame, predicate).unwrap();
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "VaporeonDELIMITER_TOKENMoltres"
+        );
+
+        assert!(filter.remove_condition(&frame, level).unwrap());
+        assert!(!filter.remove_condition(&frame, level).unwrap());
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "FlareonDELIMITER_TOKENVaporeonDELIMITER_TOKENMoltres"
+        );
+
+        // the mask follows the rows streamed in after the filter was built
+        frame.append(
+            b"Arcanine,Fire,55\nLapras,Water,40\nNinetales,Fire,35",
+            false,
+        );
+        assert_eq!(filter.matched_count(&frame), 5);
+        frame.insert_rows(0, b"Eevee,Normal,5").unwrap();
+        frame.append_remainder();
+        assert_eq!(filter.matched_count(&frame), 7);
+        assert_eq!(
+            filter.join(&frame, 4, 5)[0],
+            "ArcanineDELIMITER_TOKENLaprasDELIMITER_TOKENNinetales"
+        );
+
+        // pages follow the display order once the frame is sorted
+        let key = SortKey {
+            column: "Level".into(),
+            descending: true,
+            nulls_first: false,
+        };
+        frame.sort(vec![key]).unwrap();
+        assert_eq!(
+            filter.join(&frame, 0, 2)[0],
+            "ArcanineDELIMITER_TOKENMoltres"
+        );
+        assert_eq!(filter.join(&frame, 5, 10)[0], "FlareonDELIMITER_TOKENEevee");
+        assert!(filter.join(&frame, 7, 10)[0].is_empty());
+
+        let (_, predicate) = parse_predicate("Kind = Fire").unwrap();
+        assert_eq!(
+            filter.add_condition(&frame, predicate),
+            Err("Unknown column")
+        );
+    }
+}
@@ -1,9 +1,22 @@
 use crate::{
-    command::exec::{exec, Slice},
+    command::{
+        exec::{exec, Slice},
+        group::{value_counts, CountOrder},
+        parser::{parse_aggregation, parse_predicate},
+    },
     filter::Filter,
+    memory::{self, MemoryUsage},
+    series::{
+        errors::EditError,
+        value::{join_values, AnyValue},
+        SeriesEnum,
+    },
+    stats::Stats,
     Frame,
 };
-use js_sys::JsString;
+use js_sys::{
+    Array, BigInt64Array, Float32Array, Float64Array, Int32Array, JsString, Uint8Array, WebAssembly,
+};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -41,24 +54,316 @@ impl Frame {
             .collect()
     }
 
-    #[wasm_bindgen(method)]
-    pub fn slice(&self, offset: usize, size: usize) -> Vec<JsString> {
-        self.columns
+    /// One array of typed cells (number, bigint, boolean, string or null) per
+    /// column, rows in display order.
+    pub fn slice(&self, offset: usize, size: usize) -> Array {
+        self.slice_values(offset, size)
+            .into_iter()
+            .map(into_js_array)
+            .collect()
+    }
+
+    /// Compatibility mode of `slice` joining each column with `DELIMITER_TOKEN`.
+    #[wasm_bindgen(js_name = sliceJoined)]
+    pub fn slice_joined(&self, offset: usize, size: usize) -> Vec<JsString> {
+        self.slice_joined_values(offset, size)
             .iter()
-            .map(|column| column.join(offset, size))
             .map(|s| JsString::from(s.as_str()))
             .collect()
     }
 
-    #[wasm_bindgen(method)]
-    pub fn distinct(&self, column: &str) -> Result<JsString, JsString> {
-        let value = self
-            .find_by_name(column)
-            .distinct()
-            .map_err(|_| JsString::from("Cannot Hash Type"))?;
-        let ret = JsString::from(value.as_str());
+    /// Restores the order rows were loaded in.
+    #[wasm_bindgen(js_name = clearSort)]
+    pub fn js_clear_sort(&mut self) {
+        self.clear_sort();
+    }
+
+    pub fn rechunk(&mut self) {
+        self.columns.iter_mut().for_each(|column| column.rechunk());
+        self.track_memory();
+    }
+
+    #[wasm_bindgen(js_name = memoryUsage)]
+    pub fn js_memory_usage(&self) -> MemoryUsage {
+        self.memory_usage()
+    }
+
+    #[wasm_bindgen(js_name = columnMemoryUsage)]
+    pub fn column_memory_usage(&self, column: &str) -> Result<MemoryUsage, JsString> {
+        let position = self
+            .column_index(column)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        Ok(self.columns[position].memory_usage())
+    }
+
+    /// Typed array viewing the values of a numeric column straight out of wasm
+    /// memory, null slots hold zero. The view is invalidated as soon as wasm
+    /// memory grows, so copy it before calling back into the frame.
+    #[wasm_bindgen(js_name = columnValues)]
+    pub fn column_values(&mut self, name: &str) -> Result<JsValue, JsString> {
+        let position = self
+            .column_index(name)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        self.columns[position].rechunk();
+        self.track_memory();
+
+        let column = &self.columns[position];
+        let ret = match column.series() {
+            SeriesEnum::I32(series) => {
+                unsafe { Int32Array::view(contiguous(series.values())) }.into()
+            }
+            SeriesEnum::I64(series) => {
+                let values = contiguous(series.values());
+                let memory = wasm_bindgen::memory().unchecked_into::<WebAssembly::Memory>();
+                BigInt64Array::new_with_byte_offset_and_length(
+                    &memory.buffer(),
+                    values.as_ptr() as u32,
+                    values.len() as u32,
+                )
+                .into()
+            }
+            SeriesEnum::F32(series) => {
+                unsafe { Float32Array::view(contiguous(series.values())) }.into()
+            }
+            SeriesEnum::F64(series) => {
+                unsafe { Float64Array::view(contiguous(series.values())) }.into()
+            }
+            SeriesEnum::Bool(series) => {
+                let values = contiguous(series.values());
+                // SAFETY: bool is a single byte holding either 0 or 1
+                let bytes = unsafe {
+                    std::slice::from_raw_parts(values.as_ptr() as *const u8, values.len())
+                };
+                unsafe { Uint8Array::view(bytes) }.into()
+            }
+            SeriesEnum::I128(_) | SeriesEnum::Any(_) => {
+                return Err(JsString::from("Column has no typed array representation"))
+            }
+        };
         Ok(ret)
     }
+
+    /// Validity bitmap of a numeric column, one bit per row packed least
+    /// significant bit first. Same lifetime caveat as `columnValues`.
+    #[wasm_bindgen(js_name = columnValidity)]
+    pub fn column_validity(&mut self, name: &str) -> Result<Uint8Array, JsString> {
+        let position = self
+            .column_index(name)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        self.columns[position].rechunk();
+        self.track_memory();
+
+        let column = &self.columns[position];
+        let bytes = match column.series() {
+            SeriesEnum::I32(series) => series.validity_bytes(),
+            SeriesEnum::I64(series) => series.validity_bytes(),
+            SeriesEnum::I128(series) => series.validity_bytes(),
+            SeriesEnum::F32(series) => series.validity_bytes(),
+            SeriesEnum::F64(series) => series.validity_bytes(),
+            SeriesEnum::Bool(series) => series.validity_bytes(),
+            SeriesEnum::Any(_) => return Err(JsString::from("Column is not numeric")),
+        };
+        Ok(unsafe { Uint8Array::view(contiguous(bytes)) })
+    }
+
+    #[wasm_bindgen(js_name = setCell)]
+    pub fn js_set_cell(
+        &mut self,
+        row: usize,
+        column: &str,
+        text: &str,
+    ) -> Result<JsString, JsString> {
+        self.set_cell(row, column, text)
+            .map(|rendered| JsString::from(rendered.as_str()))
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Inserts the csv `bytes` (no header) at row `position`, returning the
+    /// number of rows added.
+    #[wasm_bindgen(js_name = insertRows)]
+    pub fn js_insert_rows(&mut self, position: usize, bytes: &[u8]) -> Result<usize, JsString> {
+        self.insert_rows(position, bytes)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = insertBlankRows)]
+    pub fn js_insert_blank_rows(&mut self, position: usize, count: usize) -> Result<(), JsString> {
+        self.insert_blank_rows(position, count)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = deleteRows)]
+    pub fn js_delete_rows(&mut self, rows: Vec<usize>) -> Result<usize, JsString> {
+        self.delete_rows(&rows)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Deletes every row matched by `filter`.
+    #[wasm_bindgen(js_name = deleteFiltered)]
+    pub fn delete_filtered(&mut self, filter: &mut Filter) -> Result<usize, JsString> {
+        let mask = filter.mask(self).to_bitvec();
+        self.delete_where(&mask)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Adds a column of blank cells. `dtype` takes the names listed by `dtypes`.
+    #[wasm_bindgen(js_name = addColumn)]
+    pub fn js_add_column(
+        &mut self,
+        name: &str,
+        dtype: &str,
+        position: usize,
+    ) -> Result<(), JsString> {
+        let dtype = dtype
+            .parse()
+            .map_err(|_| JsString::from(EditError::UnknownType.to_string()))?;
+        self.add_column(name, dtype, position)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = dropColumn)]
+    pub fn js_drop_column(&mut self, name: &str) -> Result<(), JsString> {
+        self.drop_column(name)
+            .map(|_| ())
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Converts a column to `dtype`, cells that do not parse as the new type
+    /// become null.
+    #[wasm_bindgen(js_name = castColumn)]
+    pub fn js_cast_column(&mut self, name: &str, dtype: &str) -> Result<(), JsString> {
+        let dtype = dtype
+            .parse()
+            .map_err(|_| JsString::from(EditError::UnknownType.to_string()))?;
+        self.cast_column(name, dtype)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = renameColumn)]
+    pub fn js_rename_column(&mut self, name: &str, new_name: &str) -> Result<(), JsString> {
+        self.rename_column(name, new_name)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Rearranges the columns to follow `names`, which must list every column once.
+    #[wasm_bindgen(js_name = reorderColumns)]
+    pub fn js_reorder_columns(&mut self, names: Vec<JsString>) -> Result<(), JsString> {
+        let names = names.into_iter().map(String::from).collect::<Vec<_>>();
+        self.reorder_columns(&names)
+            .map_err(|err| JsString::from(err.to_string()))
+    }
+
+    /// Reverts the latest edit, `false` when the history is empty.
+    #[wasm_bindgen(js_name = undo)]
+    pub fn js_undo(&mut self) -> Result<bool, JsString> {
+        self.undo().map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = redo)]
+    pub fn js_redo(&mut self) -> Result<bool, JsString> {
+        self.redo().map_err(|err| JsString::from(err.to_string()))
+    }
+
+    #[wasm_bindgen(getter = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.history().can_undo()
+    }
+
+    #[wasm_bindgen(getter = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.history().can_redo()
+    }
+
+    /// Number of edits kept for `undo`, older ones are forgotten.
+    #[wasm_bindgen(js_name = setHistoryLimit)]
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_mut().set_limit(limit);
+        self.track_memory();
+    }
+
+    pub fn stats(&self, column: &str) -> Result<Stats, JsString> {
+        let position = self
+            .column_index(column)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        Ok(self.columns[position].stats().clone())
+    }
+
+    pub fn distinct(&self, column: &str) -> Result<Array, JsString> {
+        let position = self
+            .column_index(column)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        Ok(into_js_array(self.columns[position].distinct()))
+    }
+
+    /// `name` is one of `sum`, `avg`, `min`, `max`, `count`, `count distinct`,
+    /// `median`, `std`, `var` or `percentile <rank>`.
+    pub fn aggregate(&self, column: &str, name: &str) -> Result<JsValue, JsString> {
+        aggregate_rows(self, column, name, None)
+    }
+
+    /// Distinct values of `column` followed by their counts and percentages,
+    /// most frequent first unless sorted `by_value`.
+    #[wasm_bindgen(js_name = valueCounts)]
+    pub fn value_counts(&self, column: &str, by_value: bool) -> Result<Array, JsString> {
+        value_counts_rows(self, column, by_value, None)
+    }
+
+    #[wasm_bindgen(js_name = distinctJoined)]
+    pub fn distinct_joined(&self, column: &str) -> Result<JsString, JsString> {
+        let position = self
+            .column_index(column)
+            .map_err(|_| JsString::from("Unknown column"))?;
+        let value = join_values(self.columns[position].distinct());
+        Ok(JsString::from(value.as_str()))
+    }
+}
+
+fn aggregate_rows(
+    frame: &Frame,
+    column: &str,
+    name: &str,
+    rows: Option<&[usize]>,
+) -> Result<JsValue, JsString> {
+    let aggregation = match parse_aggregation(name) {
+        Ok((rest, aggregation)) if rest.trim().is_empty() => aggregation,
+        _ => return Err(JsString::from("Unknown aggregation")),
+    };
+    let position = frame
+        .column_index(column)
+        .map_err(|_| JsString::from("Unknown column"))?;
+    let value = aggregation
+        .apply(&frame.columns[position], rows)
+        .map_err(JsString::from)?;
+    Ok(JsValue::from(value))
+}
+
+fn value_counts_rows(
+    frame: &Frame,
+    column: &str,
+    by_value: bool,
+    rows: Option<&[usize]>,
+) -> Result<Array, JsString> {
+    let position = frame
+        .column_index(column)
+        .map_err(|_| JsString::from("Unknown column"))?;
+    let order = match by_value {
+        true => CountOrder::Value,
+        false => CountOrder::Count,
+    };
+    let counts = value_counts(&frame.columns[position], rows, order);
+    Ok(counts
+        .slice_values(0, counts.height())
+        .into_iter()
+        .map(into_js_array)
+        .collect())
+}
+
+fn contiguous<T>(buffer: Option<&[T]>) -> &[T] {
+    buffer.expect("Column was rechunked")
+}
+
+fn into_js_array<'a>(values: impl IntoIterator<Item = AnyValue<'a>>) -> Array {
+    values.into_iter().map(JsValue::from).collect()
 }
 
 #[wasm_bindgen]
@@ -69,10 +374,44 @@ pub struct PollSource {
 
 #[wasm_bindgen]
 impl PollSource {
-    #[wasm_bindgen(method)]
-    pub fn slice(&self, frame: &Frame, offset: usize, size: usize) -> Vec<JsString> {
-        match &self.source {
+    pub fn slice(&mut self, frame: &Frame, offset: usize, size: usize) -> Array {
+        let columns = match &mut self.source {
             Slice::FilterSlice(filter) => filter.slice(frame, offset, size),
+            Slice::Sorted => frame.slice_values(offset, size),
+            Slice::Scalar(value) => vec![vec![value.clone()]],
+            Slice::Grouped(grouped) => grouped.slice_values(offset, size),
+        };
+        columns.into_iter().map(into_js_array).collect()
+    }
+
+    #[wasm_bindgen(js_name = sliceJoined)]
+    pub fn slice_joined(&mut self, frame: &Frame, offset: usize, size: usize) -> Vec<JsString> {
+        let columns = match &mut self.source {
+            Slice::FilterSlice(filter) => filter.join(frame, offset, size),
+            Slice::Sorted => frame.slice_joined_values(offset, size),
+            Slice::Scalar(value) => vec![value.to_string()],
+            Slice::Grouped(grouped) => grouped.slice_joined_values(offset, size),
+        };
+        columns.iter().map(|s| JsString::from(s.as_str())).collect()
+    }
+
+    /// Rows the source currently holds, which grows while the file streams in.
+    #[wasm_bindgen(js_name = matchedCount)]
+    pub fn matched_count(&mut self, frame: &Frame) -> usize {
+        match &mut self.source {
+            Slice::FilterSlice(filter) => filter.matched_count(frame),
+            Slice::Sorted => frame.height(),
+            Slice::Scalar(_) => 1,
+            Slice::Grouped(grouped) => grouped.height(),
+        }
+    }
+
+    /// Column names of the rows handed out by `slice`.
+    pub fn header(&self, frame: &Frame) -> Vec<JsString> {
+        match &self.source {
+            Slice::Grouped(grouped) => grouped.header(),
+            Slice::Scalar(_) => vec![JsString::from(self._type)],
+            _ => frame.header(),
         }
     }
 
@@ -82,14 +421,33 @@ impl PollSource {
 }
 
 #[wasm_bindgen(js_name = processCommand)]
-pub fn process_command(input: &str, frame: &Frame) -> PollSource {
-    let slice = exec(input, frame).unwrap();
-    match slice {
+pub fn process_command(input: &str, frame: &mut Frame) -> Result<PollSource, JsString> {
+    let slice = exec(input, frame).map_err(JsString::from)?;
+    let source = match slice {
         Slice::FilterSlice(_) => PollSource {
             _type: "filter",
             source: slice,
         },
-    }
+        Slice::Sorted => PollSource {
+            _type: "sort",
+            source: slice,
+        },
+        Slice::Scalar(_) => PollSource {
+            _type: "aggregate",
+            source: slice,
+        },
+        Slice::Grouped(_) => PollSource {
+            _type: "group",
+            source: slice,
+        },
+    };
+    Ok(source)
+}
+
+/// Heap bytes held by every frame that has not been freed yet.
+#[wasm_bindgen(js_name = totalMemoryUsage)]
+pub fn total_memory_usage() -> usize {
+    memory::total()
 }
 
 #[wasm_bindgen(js_name = newFilter)]
@@ -113,6 +471,77 @@ pub fn process_stream_tail(frame: &mut Frame) {
 }
 
 #[wasm_bindgen(js_name = addEqualtoFilter)]
-pub fn add_equalto_filter(filter: &mut Filter, frame: &Frame, bytes: &[u8], column: &str) {
-    filter.add_equalto_filter(frame, bytes, column)
+pub fn add_equalto_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    bytes: &[u8],
+    column: &str,
+) -> Result<usize, JsString> {
+    filter
+        .add_equalto_filter(frame, bytes, column)
+        .map_err(JsString::from)
+}
+
+/// `op` is one of `=`, `!=`, `<`, `<=`, `>` or `>=`.
+#[wasm_bindgen(js_name = addCompareFilter)]
+pub fn add_compare_filter(
+    filter: &mut Filter,
+    frame: &Frame,
+    bytes: &[u8],
+    column: &str,
+    op: &str,
+) -> Result<usize, JsString> {
+    let op = op
+        .parse()
+        .map_err(|_| JsString::from("Unknown comparison"))?;
+    filter
+        .add_compare_filter(frame, bytes, column, op)
+        .map_err(JsString::from)
+}
+
+/// Narrows `filter` by a condition such as `(Type = Fire or Type = Water) and
+/// not Legendary = true`, returns the id to remove it with.
+#[wasm_bindgen(js_name = addCondition)]
+pub fn add_condition(filter: &mut Filter, frame: &Frame, text: &str) -> Result<usize, JsString> {
+    let predicate = match parse_predicate(text) {
+        Ok((rest, predicate)) if rest.trim().is_empty() => predicate,
+        _ => return Err(JsString::from("Cannot parse condition")),
+    };
+    filter
+        .add_condition(frame, predicate)
+        .map_err(JsString::from)
+}
+
+#[wasm_bindgen(js_name = matchedCount)]
+pub fn matched_count(filter: &mut Filter, frame: &Frame) -> usize {
+    filter.matched_count(frame)
+}
+
+#[wasm_bindgen(js_name = removeCondition)]
+pub fn remove_condition(filter: &mut Filter, frame: &Frame, id: usize) -> Result<bool, JsString> {
+    filter.remove_condition(frame, id).map_err(JsString::from)
+}
+
+/// Aggregation over the rows matched by `filter`, see `Frame::aggregate`.
+#[wasm_bindgen(js_name = aggregateFiltered)]
+pub fn aggregate_filtered(
+    filter: &mut Filter,
+    frame: &Frame,
+    column: &str,
+    name: &str,
+) -> Result<JsValue, JsString> {
+    let rows = filter.rows(frame);
+    aggregate_rows(frame, column, name, Some(rows))
+}
+
+/// Value counts over the rows matched by `filter`, see `Frame::value_counts`.
+#[wasm_bindgen(js_name = valueCountsFiltered)]
+pub fn value_counts_filtered(
+    filter: &mut Filter,
+    frame: &Frame,
+    column: &str,
+    by_value: bool,
+) -> Result<Array, JsString> {
+    let rows = filter.rows(frame);
+    value_counts_rows(frame, column, by_value, Some(rows))
 }
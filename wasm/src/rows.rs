@@ -0,0 +1,386 @@
+use bitvec::{prelude::BitVec, slice::BitSlice};
+
+use std::{
+    cell::Cell,
+    mem::discriminant,
+    ops::Range,
+    rc::{Rc, Weak},
+};
+
+use crate::{
+    history::Operation,
+    series::{errors::EditError, SeriesEnum},
+    ChunkFromJsBytes, Frame, Words,
+};
+
+/// Change to the row set of a frame, or to the column names filters refer to.
+/// The frame keeps each edit until every filter has replayed it onto the masks
+/// computed before it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RowEdit {
+    Insert { position: usize, count: usize },
+    Delete { mask: BitVec },
+    Rename { old: String, new: String },
+}
+
+impl RowEdit {
+    /// Realigns a row mask, inserted rows are set to `inserted`.
+    pub fn apply_to_mask(&self, mask: &mut BitVec, inserted: bool) {
+        match self {
+            RowEdit::Insert { position, count } => {
+                if *position > mask.len() {
+                    return;
+                }
+                let tail = mask.split_off(*position);
+                mask.resize(position + count, inserted);
+                mask.extend_from_bitslice(&tail);
+            }
+            RowEdit::Delete { mask: removed } => {
+                *mask = mask
+                    .iter()
+                    .enumerate()
+                    .filter(|(row, _)| !removed.get(*row).is_some_and(|bit| *bit))
+                    .map(|(_, bit)| *bit)
+                    .collect();
+            }
+            RowEdit::Rename { .. } => {}
+        }
+    }
+
+    pub fn heap_size(&self) -> usize {
+        match self {
+            RowEdit::Insert { .. } => 0,
+            RowEdit::Delete { mask } => mask.capacity() / 8,
+            RowEdit::Rename { old, new } => old.capacity() + new.capacity(),
+        }
+    }
+}
+
+/// Contiguous ranges of set bits, in ascending order.
+pub fn runs(mask: &BitSlice) -> Vec<Range<usize>> {
+    let mut ret: Vec<Range<usize>> = Vec::new();
+    for row in mask.iter_ones() {
+        match ret.last_mut() {
+            Some(run) if run.end == row => run.end += 1,
+            _ => ret.push(row..row + 1),
+        }
+    }
+    ret
+}
+
+fn span(position: usize, count: usize) -> BitVec {
+    let mut mask = BitVec::repeat(false, position);
+    mask.resize(position + count, true);
+    mask
+}
+
+impl Frame {
+    /// Parses csv `bytes` (without header) into rows placed at `position`.
+    /// Missing trailing fields are left blank. Returns the number of rows added.
+    pub fn insert_rows(&mut self, position: usize, bytes: &[u8]) -> Result<usize, EditError> {
+        let position = self.insert_position(position)?;
+        if bytes.is_empty() || self.columns.is_empty() {
+            return Ok(0);
+        }
+
+        let chunk = ChunkFromJsBytes::from_bytes(bytes)
+            .with_column_number(self.columns.len())
+            .read();
+        let count = chunk
+            .buffers
+            .iter()
+            .map(Words::len)
+            .max()
+            .unwrap_or_default();
+
+        let rows = self
+            .columns
+            .iter()
+            .zip(chunk.buffers)
+            .map(|(column, mut words)| {
+                (words.len()..count).for_each(|_| words.extend(b""));
+                SeriesEnum::from_words(column.dtype(), words)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| EditError::UnknownType)?;
+
+        self.execute(Operation::InsertRows {
+            mask: span(position, count),
+            rows,
+        })?;
+        Ok(count)
+    }
+
+    /// Inserts `count` rows of empty cells, null for every typed column.
+    pub fn insert_blank_rows(&mut self, position: usize, count: usize) -> Result<(), EditError> {
+        let position = self.insert_position(position)?;
+        if count == 0 || self.columns.is_empty() {
+            return Ok(());
+        }
+
+        let rows = self
+            .columns
+            .iter()
+            .map(|column| SeriesEnum::from_words(column.dtype(), Words::blank(count)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| EditError::UnknownType)?;
+
+        self.execute(Operation::InsertRows {
+            mask: span(position, count),
+            rows,
+        })
+    }
+
+    /// Deletes the rows displayed at `rows`.
+    pub fn delete_rows(&mut self, rows: &[usize]) -> Result<usize, EditError> {
+        let height = self.height();
+        let mut mask = BitVec::repeat(false, height);
+        for &row in rows {
+            if row >= height {
+                return Err(EditError::OutOfBounds);
+            }
+            mask.set(self.physical_row(row), true);
+        }
+
+        self.delete_where(&mask)
+    }
+
+    /// Deletes the rows stored at the positions set in `mask`, rows past its end
+    /// are kept.
+    pub fn delete_where(&mut self, mask: &BitSlice) -> Result<usize, EditError> {
+        let mut mask = mask.to_bitvec();
+        mask.resize(self.height(), false);
+
+        let count = mask.count_ones();
+        if count > 0 {
+            self.execute(Operation::DeleteRows { mask })?;
+        }
+        Ok(count)
+    }
+
+    /// Drops the rows set in `mask` from every column and hands them back.
+    pub(crate) fn remove_rows(&mut self, mask: &BitSlice) -> Vec<SeriesEnum> {
+        let mut mask = mask.to_bitvec();
+        mask.resize(self.height(), false);
+
+        let removed = self
+            .columns
+            .iter_mut()
+            .map(|column| column.remove_rows(&mask))
+            .collect();
+
+        self.record_row_edit(RowEdit::Delete { mask });
+        removed
+    }
+
+    /// Slots one series of rows per column in at the positions set in `mask`.
+    /// Rows that do not match the column types are handed back untouched.
+    pub(crate) fn restore_rows(
+        &mut self,
+        mask: &BitSlice,
+        rows: Vec<SeriesEnum>,
+    ) -> Result<(), Vec<SeriesEnum>> {
+        let matching = rows.len() == self.columns.len()
+            && self
+                .columns
+                .iter()
+                .zip(rows.iter())
+                .all(|(column, rows)| discriminant(column.series()) == discriminant(rows));
+        if !matching {
+            return Err(rows);
+        }
+
+        self.columns
+            .iter_mut()
+            .zip(rows)
+            .for_each(|(column, rows)| {
+                column
+                    .restore_rows(mask, rows)
+                    .expect("Row types match the columns")
+            });
+
+        runs(mask).into_iter().for_each(|run| {
+            self.record_row_edit(RowEdit::Insert {
+                position: run.start,
+                count: run.len(),
+            })
+        });
+        Ok(())
+    }
+
+    /// Row edits recorded after the first `synced` ones.
+    pub fn row_edits_since(&self, synced: usize) -> &[RowEdit] {
+        let start = synced.saturating_sub(self.dropped_edits);
+        self.row_edits.get(start..).unwrap_or_default()
+    }
+
+    /// Marks every row edit so far as replayed by `reader`, which holds edits
+    /// back from being dropped for as long as it lives.
+    pub(crate) fn sync_reader(&self, reader: &Rc<Cell<usize>>) {
+        reader.set(self.dropped_edits + self.row_edits.len());
+        let mut readers = self.edit_readers.borrow_mut();
+        if !readers
+            .iter()
+            .any(|other| std::ptr::eq(other.as_ptr(), Rc::as_ptr(reader)))
+        {
+            readers.push(Rc::downgrade(reader));
+        }
+    }
+
+    /// Appends `edit` after dropping the edits every live reader has replayed.
+    pub(crate) fn record_row_edit(&mut self, edit: RowEdit) {
+        let recorded = self.dropped_edits + self.row_edits.len();
+        let readers = self.edit_readers.get_mut();
+        readers.retain(|reader| reader.strong_count() > 0);
+        let replayed = readers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|reader| reader.get())
+            .min()
+            .unwrap_or(recorded);
+
+        self.row_edits
+            .drain(..replayed.saturating_sub(self.dropped_edits));
+        self.dropped_edits = self.dropped_edits.max(replayed);
+        self.row_edits.push(edit);
+    }
+
+    /// Storage position for rows inserted at display `position`. A sorted frame
+    /// places them by their values, so they are simply stored last.
+    fn insert_position(&self, position: usize) -> Result<usize, EditError> {
+        match position > self.height() {
+            true => Err(EditError::OutOfBounds),
+            false if self.index.is_empty() => Ok(position),
+            false => Ok(self.height()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitvec::prelude::BitVec;
+
+    use super::RowEdit;
+    use crate::{
+        filter::Filter,
+        series::{errors::EditError, value::AnyValue},
+        ChunkFromJsBytes, Frame,
+    };
+
+    #[test]
+    fn edit_mask() {
+        let mut mask = [true, false, true].into_iter().collect::<BitVec>();
+        RowEdit::Insert {
+            position: 1,
+            count: 2,
+        }
+        .apply_to_mask(&mut mask, false);
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0, 4]);
+
+        let removed = [false, false, true, false, true].into_iter().collect();
+        RowEdit::Delete { mask: removed }.apply_to_mask(&mut mask, false);
+        assert_eq!(mask.len(), 3);
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn insert_delete() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30\nJolteon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let mut filter = Filter::default();
+        filter.add_equalto_filter(&frame, b"30", "Level").unwrap();
+
+        assert_eq!(frame.insert_rows(1, b"Eevee,5\nEspeon"), Ok(2));
+        frame.insert_blank_rows(5, 1).unwrap();
+        assert_eq!(frame.insert_blank_rows(7, 1), Err(EditError::OutOfBounds));
+        assert_eq!(frame.height(), 6);
+        assert_eq!(
+            frame.find_by_name("Name").join(0, 6),
+            "FlareonDELIMITER_TOKENEeveeDELIMITER_TOKENEspeonDELIMITER_TOKENVaporeon\
+             DELIMITER_TOKENJolteonDELIMITER_TOKEN"
+        );
+        assert_eq!(frame.find_by_name("Level").stats().null_count(), 2);
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "VaporeonDELIMITER_TOKENJolteon"
+        );
+
+        assert_eq!(frame.delete_rows(&[0, 3]), Ok(2));
+        assert_eq!(frame.height(), 4);
+        assert_eq!(filter.join(&frame, 0, 5)[0], "Jolteon");
+
+        let mask = filter.mask(&frame).to_bitvec();
+        assert_eq!(frame.delete_where(&mask), Ok(1));
+        assert_eq!(
+            frame.find_by_name("Name").join(0, 3),
+            "EeveeDELIMITER_TOKENEspeonDELIMITER_TOKEN"
+        );
+        assert_eq!(
+            frame.find_by_name("Level").stats().max(),
+            Some(&AnyValue::I32(5))
+        );
+    }
+
+    #[test]
+    fn inserted_rows_match() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30\nJolteon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let mut filter = Filter::default();
+        filter.add_equalto_filter(&frame, b"30", "Level").unwrap();
+
+        // a deleted row comes back matched once the deletion is undone
+        assert_eq!(frame.delete_rows(&[1]), Ok(1));
+        assert_eq!(filter.join(&frame, 0, 5)[0], "Jolteon");
+        frame.undo().unwrap();
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "VaporeonDELIMITER_TOKENJolteon"
+        );
+
+        assert_eq!(frame.insert_rows(0, b"Espeon,30\nEevee,5"), Ok(2));
+        assert_eq!(filter.matched_count(&frame), 3);
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "EspeonDELIMITER_TOKENVaporeonDELIMITER_TOKENJolteon"
+        );
+    }
+
+    #[test]
+    fn edits_dropped_once_replayed() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30\nJolteon,30\nEevee,5".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let mut filter = Filter::default();
+        filter.add_equalto_filter(&frame, b"30", "Level").unwrap();
+        let mut lagging = Filter::default();
+        lagging.add_equalto_filter(&frame, b"5", "Level").unwrap();
+
+        frame.delete_rows(&[0]).unwrap();
+        frame.delete_rows(&[0]).unwrap();
+        assert_eq!(filter.join(&frame, 0, 5)[0], "Jolteon");
+        frame.insert_rows(0, b"Espeon,30").unwrap();
+        assert_eq!(frame.row_edits.len(), 3);
+
+        // edits are dropped once the last filter needing them replayed them
+        assert_eq!(lagging.join(&frame, 0, 5)[0], "Eevee");
+        drop(lagging);
+        frame.delete_rows(&[2]).unwrap();
+        assert_eq!(frame.row_edits.len(), 2);
+        assert_eq!(filter.join(&frame, 0, 5)[0], "EspeonDELIMITER_TOKENJolteon");
+        frame.delete_rows(&[0]).unwrap();
+        assert_eq!(frame.row_edits.len(), 1);
+        assert_eq!(filter.join(&frame, 0, 5)[0], "Jolteon");
+
+        drop(filter);
+        frame.undo().unwrap();
+        assert_eq!(frame.row_edits.len(), 1);
+    }
+}
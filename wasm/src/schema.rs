@@ -0,0 +1,214 @@
+use crate::{
+    column::Column,
+    history::Operation,
+    series::{errors::EditError, SeriesEnum},
+    type_parser::Codes,
+    Frame, Words,
+};
+
+impl Frame {
+    pub fn column_index(&self, name: &str) -> Result<usize, EditError> {
+        self.columns
+            .iter()
+            .position(|column| column.name() == name)
+            .ok_or(EditError::UnknownColumn)
+    }
+
+    fn check_free_name(&self, name: &str) -> Result<(), EditError> {
+        match self.column_index(name) {
+            Ok(_) => Err(EditError::DuplicateColumn),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Inserts a column of blank cells at `position`, null unless `dtype` is `Any`.
+    pub fn add_column(
+        &mut self,
+        name: &str,
+        dtype: Codes,
+        position: usize,
+    ) -> Result<(), EditError> {
+        self.check_free_name(name)?;
+        if position > self.columns.len() {
+            return Err(EditError::OutOfBounds);
+        }
+
+        let series = SeriesEnum::from_words(dtype, Words::blank(self.height()))
+            .map_err(|_| EditError::UnknownType)?;
+        let column = Column::new(series, name.into(), dtype);
+        self.execute(Operation::InsertColumn { position, column })
+    }
+
+    pub fn drop_column(&mut self, name: &str) -> Result<(), EditError> {
+        let position = self.column_index(name)?;
+        self.execute(Operation::RemoveColumn { position })
+    }
+
+    pub fn rename_column(&mut self, name: &str, new_name: &str) -> Result<(), EditError> {
+        let position = self.column_index(name)?;
+        if name == new_name {
+            return Ok(());
+        }
+        self.check_free_name(new_name)?;
+
+        self.execute(Operation::Rename {
+            position,
+            name: new_name.into(),
+        })
+    }
+
+    /// Converts a column to `dtype`, cells that do not parse as the new type
+    /// become null.
+    pub fn cast_column(&mut self, name: &str, dtype: Codes) -> Result<(), EditError> {
+        let position = self.column_index(name)?;
+        if self.columns[position].dtype() == dtype {
+            return Ok(());
+        }
+
+        let column = self.columns[position].cast(dtype)?;
+        self.execute(Operation::ReplaceColumn { position, column })
+    }
+
+    /// Rearranges the columns to follow `names`, which must list every column once.
+    pub fn reorder_columns<S: AsRef<str>>(&mut self, names: &[S]) -> Result<(), EditError> {
+        if names.len() != self.columns.len() {
+            return Err(EditError::InvalidOrder);
+        }
+
+        let order = names
+            .iter()
+            .map(|name| self.column_index(name.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut seen = order.clone();
+        seen.sort_unstable();
+        seen.dedup();
+        if seen.len() != order.len() {
+            return Err(EditError::InvalidOrder);
+        }
+
+        self.execute(Operation::Reorder { order })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        filter::Filter,
+        series::{errors::EditError, value::AnyValue},
+        type_parser::Codes,
+        ChunkFromJsBytes, Frame,
+    };
+
+    #[test]
+    fn schema() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        frame.add_column("Shiny", Codes::Boolean, 1).unwrap();
+        assert_eq!(
+            frame.add_column("Level", Codes::Int32, 0),
+            Err(EditError::DuplicateColumn)
+        );
+        assert_eq!(
+            frame.add_column("Type", Codes::Null, 0),
+            Err(EditError::UnknownType)
+        );
+        assert_eq!(frame.width(), 3);
+        assert_eq!(
+            frame.find_by_name("Shiny").slice(0, 2),
+            vec![AnyValue::Null, AnyValue::Null]
+        );
+        assert_eq!(frame.set_cell(1, "Shiny", "true"), Ok("true".into()));
+
+        assert_eq!(
+            frame.rename_column("Name", "Level"),
+            Err(EditError::DuplicateColumn)
+        );
+        frame.rename_column("Name", "Pokemon").unwrap();
+        assert_eq!(frame.column_index("Name"), Err(EditError::UnknownColumn));
+
+        assert_eq!(
+            frame.reorder_columns(&["Level", "Level", "Shiny"]),
+            Err(EditError::InvalidOrder)
+        );
+        frame
+            .reorder_columns(&["Level", "Pokemon", "Shiny"])
+            .unwrap();
+        let names = frame.columns.iter().map(|c| c.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["Level", "Pokemon", "Shiny"]);
+
+        frame.cast_column("Level", Codes::Float32).unwrap();
+        assert_eq!(frame.find_by_name("Level").dtype(), Codes::Float32);
+        assert_eq!(
+            frame.find_by_name("Level").get(1),
+            Some(AnyValue::F32(30.0))
+        );
+
+        frame.drop_column("Level").unwrap();
+        assert_eq!(frame.width(), 2);
+        assert_eq!(frame.column_index("Shiny"), Ok(1));
+    }
+
+    #[test]
+    fn edit_while_streaming() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        frame.add_column("Shiny", Codes::Boolean, 0).unwrap();
+        frame.append(b"Jolteon,30\nEevee,5", false);
+        assert_eq!(
+            frame.slice_values(2, 1),
+            vec![
+                vec![AnyValue::Null],
+                vec![AnyValue::Str("Jolteon".into())],
+                vec![AnyValue::I32(30)],
+            ]
+        );
+
+        frame.reorder_columns(&["Level", "Name", "Shiny"]).unwrap();
+        frame.append(b"Espeon,40\nUmbreon,12", false);
+        assert_eq!(
+            frame.find_by_name("Name").join(3, 2),
+            "EeveeDELIMITER_TOKENEspeon"
+        );
+
+        frame.drop_column("Name").unwrap();
+        frame.append_remainder();
+        assert_eq!(
+            frame.find_by_name("Level").slice(0, 6),
+            [25, 30, 30, 5, 40, 12].map(AnyValue::I32).to_vec()
+        );
+        assert!(frame.columns.iter().all(|column| column.len() == 6));
+    }
+
+    #[test]
+    fn filters_follow_rename() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,30".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        let mut filter = Filter::default();
+        let id = filter.add_equalto_filter(&frame, b"30", "Level").unwrap();
+        frame.rename_column("Level", "Rank").unwrap();
+
+        // streamed and inserted rows are matched against the renamed column
+        frame.append(b"Jolteon,30\nUmbreon,30", false);
+        frame.insert_rows(0, b"Espeon,30").unwrap();
+        assert_eq!(
+            filter.join(&frame, 0, 5)[0],
+            "EspeonDELIMITER_TOKENVaporeonDELIMITER_TOKENJolteon"
+        );
+
+        frame.undo().unwrap();
+        frame.undo().unwrap();
+        frame.append_remainder();
+        assert_eq!(filter.matched_count(&frame), 3);
+        assert_eq!(filter.remove_condition(&frame, id), Ok(true));
+        assert_eq!(filter.matched_count(&frame), 4);
+    }
+}
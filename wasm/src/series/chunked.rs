@@ -0,0 +1,369 @@
+use bitvec::{prelude::BitVec, slice::BitSlice};
+use regex::Regex;
+
+use crate::{memory::MemoryUsage, rows::runs, Words};
+
+use super::{primitive::PrimitiveArray, utf8::Utf8Series, Primitive};
+
+/// Storage unit of a `ChunkedArray`. Chunks are never grown once pushed, only
+/// merged together by `ChunkedArray::rechunk`.
+pub trait Chunk: Default {
+    fn len(&self) -> usize;
+    fn from_words(words: Words) -> Self;
+    fn append(&mut self, other: Self);
+    fn split_off(&mut self, at: usize) -> Self;
+    fn partition(&self, mask: &BitSlice) -> (Self, Self);
+    fn memory_usage(&self) -> MemoryUsage;
+    /// Set for the cells holding a value.
+    fn validity(&self) -> &BitSlice;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Primitive> Chunk for PrimitiveArray<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn from_words(words: Words) -> Self {
+        PrimitiveArray::from_words(words)
+    }
+
+    fn append(&mut self, other: Self) {
+        self.append(other)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        self.split_off(at)
+    }
+
+    fn partition(&self, mask: &BitSlice) -> (Self, Self) {
+        self.partition(mask)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        self.memory_usage()
+    }
+
+    fn validity(&self) -> &BitSlice {
+        self.validity()
+    }
+}
+
+impl Chunk for Utf8Series {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn from_words(words: Words) -> Self {
+        Utf8Series::from_words(words)
+    }
+
+    fn append(&mut self, other: Self) {
+        self.append(other)
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        self.split_off(at)
+    }
+
+    fn partition(&self, mask: &BitSlice) -> (Self, Self) {
+        self.partition(mask)
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        self.memory_usage()
+    }
+
+    fn validity(&self) -> &BitSlice {
+        self.validity()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChunkedArray<A> {
+    chunks: Vec<A>,
+    len: usize,
+}
+
+impl<A: Chunk> From<A> for ChunkedArray<A> {
+    fn from(chunk: A) -> Self {
+        let mut ret = Self::default();
+        ret.push(chunk);
+        ret
+    }
+}
+
+impl<A: Chunk> ChunkedArray<A> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn chunks(&self) -> &[A] {
+        &self.chunks
+    }
+
+    pub fn validity(&self) -> BitVec {
+        let mut ret = BitVec::with_capacity(self.len);
+        self.chunks
+            .iter()
+            .for_each(|chunk| ret.extend_from_bitslice(chunk.validity()));
+        ret
+    }
+
+    pub fn push(&mut self, chunk: A) {
+        if chunk.is_empty() {
+            return;
+        }
+        self.len += chunk.len();
+        self.chunks.push(chunk);
+    }
+
+    /// Copy of the rows from `offset` onwards.
+    pub fn tail(&self, offset: usize) -> Self
+    where
+        A: Clone,
+    {
+        let mut ret = Self::default();
+        self.chunks_from(offset).for_each(|(chunk, start)| {
+            let mut chunk = chunk.clone();
+            match start {
+                0 => ret.push(chunk),
+                start => ret.push(chunk.split_off(start)),
+            }
+        });
+        ret
+    }
+
+    pub fn push_words(&mut self, words: Words) {
+        self.push(A::from_words(words))
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.chunks.iter().map(Chunk::memory_usage).sum()
+    }
+
+    /// Places `chunk` so that its first row ends up at `position`, splitting the
+    /// chunk currently holding that row in two.
+    pub fn insert(&mut self, mut position: usize, chunk: A) {
+        let at = self.chunks.iter().position(|current| {
+            let found = position <= current.len();
+            if !found {
+                position -= current.len();
+            }
+            found
+        });
+
+        match at {
+            Some(i) => {
+                let tail = self.chunks[i].split_off(position);
+                self.len += chunk.len();
+                let parts = [chunk, tail].into_iter().filter(|part| !part.is_empty());
+                self.chunks.splice(i + 1..i + 1, parts);
+                self.chunks.retain(|chunk| !chunk.is_empty());
+            }
+            None => self.push(chunk),
+        }
+    }
+
+    /// Drops the rows set in `mask` and hands them back as a single chunk.
+    pub fn remove(&mut self, mask: &BitSlice) -> A {
+        let mut removed = A::default();
+        let mut start = 0;
+
+        for chunk in self.chunks.iter_mut() {
+            let end = start + chunk.len();
+            let chunk_mask = &mask[start.min(mask.len())..end.min(mask.len())];
+            start = end;
+
+            if chunk_mask.not_any() {
+                continue;
+            }
+            let (kept, taken) = chunk.partition(chunk_mask);
+            *chunk = kept;
+            removed.append(taken);
+        }
+
+        self.chunks.retain(|chunk| !chunk.is_empty());
+        self.len -= removed.len();
+        removed
+    }
+
+    /// Inverse of `remove`, slots the rows of `removed` back at the positions set
+    /// in `mask`.
+    pub fn restore(&mut self, mask: &BitSlice, removed: Self) {
+        let mut removed = removed.into_chunk();
+        for run in runs(mask) {
+            let rest = removed.split_off(run.len());
+            self.insert(run.start, std::mem::replace(&mut removed, rest));
+        }
+        self.rechunk();
+    }
+
+    fn into_chunk(mut self) -> A {
+        self.rechunk();
+        self.chunks.pop().unwrap_or_default()
+    }
+
+    /// Merges every chunk into a single contiguous one.
+    pub fn rechunk(&mut self) {
+        if self.chunks.len() <= 1 {
+            return;
+        }
+
+        let mut chunks = std::mem::take(&mut self.chunks).into_iter();
+        let mut merged = chunks.next().unwrap_or_default();
+        chunks.for_each(|chunk| merged.append(chunk));
+        self.chunks.push(merged);
+    }
+
+    /// Chunk holding the row `index` together with the position inside it.
+    pub fn locate(&self, mut index: usize) -> Option<(&A, usize)> {
+        for chunk in self.chunks.iter() {
+            if index < chunk.len() {
+                return Some((chunk, index));
+            }
+            index -= chunk.len();
+        }
+        None
+    }
+
+    pub fn locate_mut(&mut self, mut index: usize) -> Option<(&mut A, usize)> {
+        for chunk in self.chunks.iter_mut() {
+            if index < chunk.len() {
+                return Some((chunk, index));
+            }
+            index -= chunk.len();
+        }
+        None
+    }
+
+    /// Chunks starting at row `offset`, each paired with the first position to read.
+    pub fn chunks_from(&self, mut offset: usize) -> impl Iterator<Item = (&A, usize)> {
+        self.chunks.iter().filter_map(move |chunk| {
+            let start = offset.min(chunk.len());
+            offset -= start;
+            (start < chunk.len()).then_some((chunk, start))
+        })
+    }
+}
+
+impl<T: Primitive> From<Vec<Option<T>>> for ChunkedArray<PrimitiveArray<T>> {
+    fn from(values: Vec<Option<T>>) -> Self {
+        PrimitiveArray::from(values).into()
+    }
+}
+
+impl<T: Primitive> ChunkedArray<PrimitiveArray<T>> {
+    /// Contiguous values buffer, only available while the array holds at most one chunk.
+    pub fn values(&self) -> Option<&[T]> {
+        match self.chunks.as_slice() {
+            [] => Some(&[]),
+            [chunk] => Some(chunk.values()),
+            _ => None,
+        }
+    }
+
+    /// Packed validity bitmap, only available while the array holds at most one chunk.
+    pub fn validity_bytes(&self) -> Option<&[u8]> {
+        match self.chunks.as_slice() {
+            [] => Some(&[]),
+            [chunk] => Some(chunk.validity_bytes()),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<T>> + '_ {
+        self.chunks.iter().flat_map(PrimitiveArray::iter)
+    }
+
+    pub fn iter_from(&self, offset: usize) -> impl Iterator<Item = Option<T>> + '_ {
+        self.chunks_from(offset)
+            .flat_map(|(chunk, start)| chunk.iter_from(start))
+    }
+}
+
+impl ChunkedArray<Utf8Series> {
+    pub fn iter(&self) -> impl Iterator<Item = Option<&str>> {
+        self.chunks.iter().flat_map(Utf8Series::iter)
+    }
+
+    pub fn iter_from(&self, offset: usize) -> impl Iterator<Item = Option<&str>> {
+        self.chunks_from(offset)
+            .flat_map(|(chunk, start)| chunk.iter().skip(start))
+    }
+
+    /// Cells in which `regex` finds a match, null cells never match.
+    pub fn matches(&self, regex: &Regex) -> BitVec {
+        self.iter()
+            .map(|el| el.is_some_and(|el| regex.is_match(el)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitvec::prelude::BitVec;
+
+    use super::ChunkedArray;
+    use crate::{series::primitive::PrimitiveArray, Words};
+
+    #[test]
+    fn rechunk() {
+        let mut array = ChunkedArray::from(vec![Some(1), None]);
+        let mut words = Words::default();
+        words.extend(b"3");
+        words.extend(b"4");
+        array.push_words(words);
+
+        assert_eq!(array.chunks().len(), 2);
+        assert_eq!(array.locate(2).map(|(_, i)| i), Some(0));
+        assert_eq!(
+            array.iter_from(1).collect::<Vec<_>>(),
+            vec![None, Some(3), Some(4)]
+        );
+
+        array.rechunk();
+        assert_eq!(array.chunks().len(), 1);
+        assert_eq!(array.len(), 4);
+        assert_eq!(
+            array.chunks()[0],
+            PrimitiveArray::from(vec![Some(1), None, Some(3), Some(4)])
+        );
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut array = ChunkedArray::from(vec![Some(1), Some(2)]);
+        array.push(PrimitiveArray::from(vec![Some(5)]));
+        array.insert(1, PrimitiveArray::from(vec![None, Some(9)]));
+        array.insert(5, PrimitiveArray::from(vec![Some(6)]));
+
+        assert_eq!(array.len(), 6);
+        assert_eq!(
+            array.iter().collect::<Vec<_>>(),
+            vec![Some(1), None, Some(9), Some(2), Some(5), Some(6)]
+        );
+
+        let mask = [true, false, true, false, false, true]
+            .into_iter()
+            .collect::<BitVec>();
+        let removed = array.remove(&mask);
+
+        assert_eq!(
+            removed,
+            PrimitiveArray::from(vec![Some(1), Some(9), Some(6)])
+        );
+        assert_eq!(array.len(), 3);
+        assert_eq!(
+            array.iter().collect::<Vec<_>>(),
+            vec![None, Some(2), Some(5)]
+        );
+    }
+}
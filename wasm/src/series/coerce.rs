@@ -0,0 +1,118 @@
+use std::borrow::Cow;
+
+use crate::type_parser::Codes;
+
+use super::{errors::WrongType, Primitive, PrimitiveSeries, SeriesEnum};
+
+/// Type both operands are promoted to before being compared. Integers widen to
+/// the wider width and meet floats as `Float64`, booleans and strings only
+/// compare with themselves.
+pub fn common_type(left: Codes, right: Codes) -> Result<Codes, WrongType> {
+    use Codes::*;
+
+    match (left, right) {
+        (left, right) if left == right => Ok(left),
+        (Int32 | Int64 | Int128, Int32 | Int64 | Int128) => Ok(left.max(right)),
+        (
+            Int32 | Int64 | Int128 | Float32 | Float64,
+            Int32 | Int64 | Int128 | Float32 | Float64,
+        ) => Ok(Float64),
+        _ => Err(WrongType),
+    }
+}
+
+fn map_values<T: Primitive, U: Primitive>(
+    series: &PrimitiveSeries<T>,
+    f: impl Fn(T) -> U,
+) -> PrimitiveSeries<U> {
+    series
+        .iter()
+        .map(|el| el.map(&f))
+        .collect::<Vec<_>>()
+        .into()
+}
+
+impl SeriesEnum {
+    /// The series widened to `code`, borrowed when it already has that type.
+    pub fn promote(&self, code: Codes) -> Result<Cow<'_, SeriesEnum>, WrongType> {
+        if self.dtype() == code {
+            return Ok(Cow::Borrowed(self));
+        }
+
+        let ret = match (self, code) {
+            (SeriesEnum::I32(series), Codes::Int64) => {
+                SeriesEnum::I64(map_values(series, i64::from))
+            }
+            (SeriesEnum::I32(series), Codes::Int128) => {
+                SeriesEnum::I128(map_values(series, i128::from))
+            }
+            (SeriesEnum::I64(series), Codes::Int128) => {
+                SeriesEnum::I128(map_values(series, i128::from))
+            }
+            (SeriesEnum::I32(series), Codes::Float64) => {
+                SeriesEnum::F64(map_values(series, f64::from))
+            }
+            (SeriesEnum::I64(series), Codes::Float64) => {
+                SeriesEnum::F64(map_values(series, |el| el as f64))
+            }
+            (SeriesEnum::I128(series), Codes::Float64) => {
+                SeriesEnum::F64(map_values(series, |el| el as f64))
+            }
+            (SeriesEnum::F32(series), Codes::Float64) => {
+                SeriesEnum::F64(map_values(series, f64::from))
+            }
+            _ => return Err(WrongType),
+        };
+        Ok(Cow::Owned(ret))
+    }
+
+    /// Both series promoted to their common type.
+    pub fn coerce<'a>(
+        &'a self,
+        other: &'a SeriesEnum,
+    ) -> Result<(Cow<'a, SeriesEnum>, Cow<'a, SeriesEnum>), WrongType> {
+        let code = common_type(self.dtype(), other.dtype())?;
+        Ok((self.promote(code)?, other.promote(code)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::common_type;
+    use crate::{
+        series::{errors::WrongType, CompareOp, SeriesEnum, SeriesTrait},
+        type_parser::Codes,
+    };
+
+    #[test]
+    fn coercion() {
+        assert_eq!(common_type(Codes::Int32, Codes::Int128), Ok(Codes::Int128));
+        assert_eq!(
+            common_type(Codes::Int64, Codes::Float32),
+            Ok(Codes::Float64)
+        );
+        assert_eq!(
+            common_type(Codes::Float32, Codes::Float32),
+            Ok(Codes::Float32)
+        );
+        assert_eq!(common_type(Codes::Boolean, Codes::Int32), Err(WrongType));
+        assert_eq!(common_type(Codes::Any, Codes::Float64), Err(WrongType));
+
+        let levels = SeriesEnum::I32(vec![Some(25), None, Some(30)].into());
+        let value = SeriesEnum::F64(vec![Some(27.5)].into());
+        let mask = levels.compare(CompareOp::Greater, &value).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![2]);
+
+        let wide = SeriesEnum::I64(vec![Some(5_000_000_000)].into());
+        let mask = levels.compare(CompareOp::Less, &wide).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0, 2]);
+
+        let low = SeriesEnum::I128(vec![Some(20)].into());
+        let high = SeriesEnum::F32(vec![Some(25.5)].into());
+        let mask = levels.between(&low, &high).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0]);
+
+        let flags = SeriesEnum::Bool(vec![Some(true)].into());
+        assert_eq!(levels.equal_to(&flags), Err(WrongType));
+    }
+}
@@ -2,25 +2,44 @@ use core::fmt;
 
 use bitvec::prelude::BitVec;
 
-#[derive(Debug)]
-pub struct NonHashable;
+use super::{chunked::ChunkedArray, utf8::Utf8Series, PrimitiveSeries};
 
-impl fmt::Display for NonHashable {
+#[derive(Debug, PartialEq, Eq)]
+pub struct WrongType;
+
+impl fmt::Display for WrongType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Cannot hash column")
+        write!(f, "Unexpected type")
     }
 }
 
-#[derive(Debug)]
-pub struct WrongType;
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditError {
+    OutOfBounds,
+    InvalidValue,
+    UnknownColumn,
+    DuplicateColumn,
+    UnknownType,
+    InvalidOrder,
+}
 
-impl fmt::Display for WrongType {
+impl fmt::Display for EditError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Unexpected type")
+        match self {
+            EditError::OutOfBounds => write!(f, "Row out of bounds"),
+            EditError::InvalidValue => write!(f, "Value does not match the column type"),
+            EditError::UnknownColumn => write!(f, "No column with this name"),
+            EditError::DuplicateColumn => write!(f, "A column with this name already exists"),
+            EditError::UnknownType => write!(f, "Unknown column type"),
+            EditError::InvalidOrder => write!(f, "Column order must list every column once"),
+        }
     }
 }
 
-type View<'a, T> = &'a [Option<T>];
+pub type EditResult = Result<(), EditError>;
+
+type View<'a, T> = &'a PrimitiveSeries<T>;
 pub type ViewResult<'a, T> = Result<View<'a, T>, WrongType>;
+pub type StrViewResult<'a> = Result<&'a ChunkedArray<Utf8Series>, WrongType>;
 
 pub type FilterResult<'a> = Result<BitVec, WrongType>;
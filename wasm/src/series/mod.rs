@@ -1,176 +1,489 @@
+pub mod chunked;
+pub mod coerce;
 pub mod errors;
 pub mod macros;
+pub mod primitive;
+pub mod utf8;
+pub mod value;
 
-use bitvec::slice::BitSlice;
+use std::{cmp::Ordering, collections::HashSet, fmt::Display, hash::Hash, str::FromStr};
+
+use bitvec::{prelude::BitVec, slice::BitSlice};
 use lexical::parse;
 use num::Num;
+use ordered_float::OrderedFloat;
+use regex::Regex;
 
 use crate::{
-    distinct, equal_to_series, filter_join, join_series, sum_series, type_parser::bytes_to_bool,
+    apply_series,
+    memory::MemoryUsage,
+    type_parser::{bytes_to_bool, parse_bool, parse_type, parse_utf8, Codes},
     Words,
 };
 
-use self::errors::{FilterResult, NonHashable, ViewResult, WrongType};
+use self::{
+    chunked::ChunkedArray,
+    coerce::common_type,
+    errors::{EditError, EditResult, FilterResult, StrViewResult, ViewResult, WrongType},
+    primitive::PrimitiveArray,
+    utf8::Utf8Series,
+    value::{join_values, AnyValue},
+};
 
 pub const DELIMITER_TOKEN: &str = "DELIMITER_TOKEN";
 
-pub trait Numeric: Copy + Default + Num {}
+/// Fixed width cell types stored as chunks of `PrimitiveArray<T>`.
+pub trait Primitive: Copy + Default + PartialOrd + Display {
+    type Key: Hash + Eq;
+
+    fn key(self) -> Self::Key;
+    fn into_value(self) -> AnyValue<'static>;
+    /// The value held by `value` when it is of this type.
+    fn from_value(value: &AnyValue) -> Option<Self>;
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+    fn view(series: &SeriesEnum) -> ViewResult<Self>;
+}
+
+pub trait Numeric: Primitive + Num {}
 impl Numeric for i32 {}
 impl Numeric for i64 {}
 impl Numeric for i128 {}
 impl Numeric for f32 {}
 impl Numeric for f64 {}
 
-pub trait SeriesTrait {
-    fn len(&self) -> usize;
-    fn is_empty(&self) -> bool;
-    fn extend_from_words(&mut self, words: Words);
-    fn join(&self, offset: usize, size: usize) -> String;
-    fn sum(&self) -> Result<Box<dyn SeriesTrait>, &str> {
-        Err("Cannot sum this type")
+macro_rules! impl_primitive {
+    ($t:ty, $key:ty, $to_key:expr, $variant:ident) => {
+        impl Primitive for $t {
+            type Key = $key;
+
+            fn key(self) -> Self::Key {
+                $to_key(self)
+            }
+
+            fn into_value(self) -> AnyValue<'static> {
+                AnyValue::$variant(self)
+            }
+
+            fn from_value(value: &AnyValue) -> Option<Self> {
+                match *value {
+                    AnyValue::$variant(value) => Some(value),
+                    _ => None,
+                }
+            }
+
+            fn from_bytes(bytes: &[u8]) -> Option<Self> {
+                parse(bytes).ok()
+            }
+
+            fn view(series: &SeriesEnum) -> ViewResult<Self> {
+                match series {
+                    SeriesEnum::$variant(values) => Ok(values),
+                    _ => Err(WrongType),
+                }
+            }
+        }
+    };
+}
+
+impl_primitive!(i32, i32, std::convert::identity, I32);
+impl_primitive!(i64, i64, std::convert::identity, I64);
+impl_primitive!(i128, i128, std::convert::identity, I128);
+impl_primitive!(f32, OrderedFloat<f32>, OrderedFloat, F32);
+impl_primitive!(f64, OrderedFloat<f64>, OrderedFloat, F64);
+
+impl Primitive for bool {
+    type Key = bool;
+
+    fn key(self) -> Self::Key {
+        self
     }
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String;
-    fn equal_to(&self, _other: &dyn SeriesTrait) -> FilterResult {
-        Err(WrongType)
+
+    fn into_value(self) -> AnyValue<'static> {
+        AnyValue::Bool(self)
     }
-    fn i32(&self) -> ViewResult<i32> {
-        Err(WrongType)
+
+    fn from_value(value: &AnyValue) -> Option<Self> {
+        match *value {
+            AnyValue::Bool(value) => Some(value),
+            _ => None,
+        }
     }
-    fn i64(&self) -> ViewResult<i64> {
-        Err(WrongType)
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        bytes_to_bool(bytes)
     }
-    fn i128(&self) -> ViewResult<i128> {
-        Err(WrongType)
+
+    fn view(series: &SeriesEnum) -> ViewResult<Self> {
+        match series {
+            SeriesEnum::Bool(values) => Ok(values),
+            _ => Err(WrongType),
+        }
     }
-    fn f32(&self) -> ViewResult<f32> {
-        Err(WrongType)
+}
+
+/// Comparison of every cell against a single value. Null cells, or a null
+/// value, never match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl CompareOp {
+    pub fn matches(self, ord: Option<Ordering>) -> bool {
+        match (self, ord) {
+            (_, None) => false,
+            (CompareOp::Equal, Some(ord)) => ord.is_eq(),
+            (CompareOp::NotEqual, Some(ord)) => ord.is_ne(),
+            (CompareOp::Less, Some(ord)) => ord.is_lt(),
+            (CompareOp::LessEqual, Some(ord)) => ord.is_le(),
+            (CompareOp::Greater, Some(ord)) => ord.is_gt(),
+            (CompareOp::GreaterEqual, Some(ord)) => ord.is_ge(),
+        }
+    }
+
+    fn mask<V: PartialOrd>(
+        self,
+        cells: impl Iterator<Item = Option<V>>,
+        value: Option<V>,
+    ) -> BitVec {
+        cells
+            .map(|cell| match (&cell, &value) {
+                (Some(cell), Some(value)) => self.matches(cell.partial_cmp(value)),
+                _ => false,
+            })
+            .collect()
     }
-    fn f64(&self) -> ViewResult<f64> {
-        Err(WrongType)
+}
+
+/// Cells within `low..=high`, a null cell or bound never matches.
+fn range_mask<V: PartialOrd>(
+    cells: impl Iterator<Item = Option<V>>,
+    low: Option<V>,
+    high: Option<V>,
+) -> BitVec {
+    cells
+        .map(|cell| match (&cell, &low, &high) {
+            (Some(cell), Some(low), Some(high)) => low <= cell && cell <= high,
+            _ => false,
+        })
+        .collect()
+}
+
+impl FromStr for CompareOp {
+    type Err = ();
+
+    fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+        match symbol {
+            "=" => Ok(CompareOp::Equal),
+            "!=" => Ok(CompareOp::NotEqual),
+            "<" => Ok(CompareOp::Less),
+            "<=" => Ok(CompareOp::LessEqual),
+            ">" => Ok(CompareOp::Greater),
+            ">=" => Ok(CompareOp::GreaterEqual),
+            _ => Err(()),
+        }
     }
-    fn bool(&self) -> ViewResult<bool> {
-        Err(WrongType)
+}
+
+pub trait SeriesTrait {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool;
+    fn extend_from_words(&mut self, words: Words);
+    fn rechunk(&mut self);
+    fn memory_usage(&self) -> MemoryUsage;
+    fn get(&self, index: usize) -> Option<AnyValue<'_>>;
+    /// Overwrites a cell with the parsed `bytes`, an empty input clears numeric cells.
+    fn set(&mut self, index: usize, bytes: &[u8]) -> EditResult;
+    /// Overwrites a cell with `value`, which is null or of the series type.
+    fn set_value(&mut self, index: usize, value: &AnyValue) -> EditResult;
+    /// Parses `words` into new rows starting at `position`.
+    fn insert_words(&mut self, position: usize, words: Words);
+    /// Drops the rows set in `mask`, returning them as a new series.
+    fn remove_rows(&mut self, mask: &BitSlice) -> Self
+    where
+        Self: Sized;
+    /// Copy of the rows from `offset` onwards.
+    fn tail(&self, offset: usize) -> Self
+    where
+        Self: Sized;
+    fn slice(&self, offset: usize, size: usize) -> Vec<AnyValue<'_>>;
+    fn equal_to(&self, other: &SeriesEnum) -> FilterResult;
+    /// Compares every cell with the first value of `other`, strings compare
+    /// lexicographically.
+    fn compare(&self, op: CompareOp, other: &SeriesEnum) -> FilterResult<'_>;
+    /// Numeric cells within `tolerance` of the first value of `other`.
+    fn equal_within(&self, other: &SeriesEnum, tolerance: f64) -> FilterResult<'_>;
+    /// Cells between the first values of `low` and `high`, both included.
+    fn between(&self, low: &SeriesEnum, high: &SeriesEnum) -> FilterResult<'_>;
+    /// Set for the cells holding a value, cleared for nulls.
+    fn validity(&self) -> BitVec;
+    fn distinct(&self) -> Vec<AnyValue<'_>>;
+    /// Distinct values among the cells at `rows`, null past the end.
+    fn distinct_at(&self, rows: &[usize]) -> Vec<AnyValue<'_>>;
+
+    fn take(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        rows.iter()
+            .map(|&row| self.get(row).unwrap_or(AnyValue::Null))
+            .collect()
     }
-    fn str(&self) -> ViewResult<String> {
-        Err(WrongType)
+
+    fn join(&self, offset: usize, size: usize) -> String {
+        join_values(self.slice(offset, size))
     }
-    fn distinct(&self) -> Result<String, NonHashable> {
-        Err(NonHashable)
+}
+
+pub type PrimitiveSeries<T> = ChunkedArray<PrimitiveArray<T>>;
+
+#[derive(Clone)]
+pub enum SeriesEnum {
+    I32(PrimitiveSeries<i32>),
+    I64(PrimitiveSeries<i64>),
+    I128(PrimitiveSeries<i128>),
+    F32(PrimitiveSeries<f32>),
+    F64(PrimitiveSeries<f64>),
+    Bool(PrimitiveSeries<bool>),
+    Any(ChunkedArray<Utf8Series>),
+}
+
+impl SeriesEnum {
+    /// Parses `words` as a single chunk of the concrete type `code`.
+    pub fn from_words(code: Codes, words: Words) -> Result<Self, WrongType> {
+        match code {
+            Codes::Boolean => Ok(SeriesEnum::Bool(parse_bool(words).into())),
+            Codes::Int32 => Ok(SeriesEnum::I32(parse_type::<i32>(words).into())),
+            Codes::Int64 => Ok(SeriesEnum::I64(parse_type::<i64>(words).into())),
+            Codes::Int128 => Ok(SeriesEnum::I128(parse_type::<i128>(words).into())),
+            Codes::Float32 => Ok(SeriesEnum::F32(parse_type::<f32>(words).into())),
+            Codes::Float64 => Ok(SeriesEnum::F64(parse_type::<f64>(words).into())),
+            Codes::Any => Ok(SeriesEnum::Any(parse_utf8(words).into())),
+            Codes::Null | Codes::TmpInt | Codes::TmpFloat => Err(WrongType),
+        }
+    }
+
+    /// Single chunk of the concrete type `code` holding `values`, which are
+    /// either null or of that type.
+    pub fn from_values(code: Codes, values: &[AnyValue]) -> Result<Self, WrongType> {
+        match code {
+            Codes::Boolean => Ok(SeriesEnum::Bool(typed_values(values)?.into())),
+            Codes::Int32 => Ok(SeriesEnum::I32(typed_values(values)?.into())),
+            Codes::Int64 => Ok(SeriesEnum::I64(typed_values(values)?.into())),
+            Codes::Int128 => Ok(SeriesEnum::I128(typed_values(values)?.into())),
+            Codes::Float32 => Ok(SeriesEnum::F32(typed_values(values)?.into())),
+            Codes::Float64 => Ok(SeriesEnum::F64(typed_values(values)?.into())),
+            Codes::Any => {
+                let mut series = Utf8Series::default();
+                for value in values {
+                    match value {
+                        AnyValue::Null => series.push(None),
+                        AnyValue::Str(value) => series.push(Some(value)),
+                        _ => return Err(WrongType),
+                    }
+                }
+                Ok(SeriesEnum::Any(series.into()))
+            }
+            Codes::Null | Codes::TmpInt | Codes::TmpFloat => Err(WrongType),
+        }
+    }
+
+    pub fn dtype(&self) -> Codes {
+        match self {
+            SeriesEnum::I32(_) => Codes::Int32,
+            SeriesEnum::I64(_) => Codes::Int64,
+            SeriesEnum::I128(_) => Codes::Int128,
+            SeriesEnum::F32(_) => Codes::Float32,
+            SeriesEnum::F64(_) => Codes::Float64,
+            SeriesEnum::Bool(_) => Codes::Boolean,
+            SeriesEnum::Any(_) => Codes::Any,
+        }
+    }
+
+    pub fn i32(&self) -> ViewResult<i32> {
+        i32::view(self)
+    }
+
+    pub fn i64(&self) -> ViewResult<i64> {
+        i64::view(self)
+    }
+
+    pub fn i128(&self) -> ViewResult<i128> {
+        i128::view(self)
+    }
+
+    pub fn f32(&self) -> ViewResult<f32> {
+        f32::view(self)
+    }
+
+    pub fn f64(&self) -> ViewResult<f64> {
+        f64::view(self)
+    }
+
+    pub fn bool(&self) -> ViewResult<bool> {
+        bool::view(self)
+    }
+
+    pub fn str(&self) -> StrViewResult {
+        match self {
+            SeriesEnum::Any(series) => Ok(series),
+            _ => Err(WrongType),
+        }
+    }
+
+    pub fn matches(&self, regex: &Regex) -> FilterResult<'_> {
+        Ok(self.str()?.matches(regex))
+    }
+
+    /// Slots rows handed back by `remove_rows` in at the positions set in `mask`.
+    pub fn restore_rows(&mut self, mask: &BitSlice, rows: SeriesEnum) -> Result<(), WrongType> {
+        match (self, rows) {
+            (SeriesEnum::I32(series), SeriesEnum::I32(rows)) => series.restore(mask, rows),
+            (SeriesEnum::I64(series), SeriesEnum::I64(rows)) => series.restore(mask, rows),
+            (SeriesEnum::I128(series), SeriesEnum::I128(rows)) => series.restore(mask, rows),
+            (SeriesEnum::F32(series), SeriesEnum::F32(rows)) => series.restore(mask, rows),
+            (SeriesEnum::F64(series), SeriesEnum::F64(rows)) => series.restore(mask, rows),
+            (SeriesEnum::Bool(series), SeriesEnum::Bool(rows)) => series.restore(mask, rows),
+            (SeriesEnum::Any(series), SeriesEnum::Any(rows)) => series.restore(mask, rows),
+            _ => return Err(WrongType),
+        }
+        Ok(())
+    }
+
+    pub fn sum(&self) -> Result<SeriesEnum, &str> {
+        match self {
+            SeriesEnum::I32(values) => Ok(SeriesEnum::I32(vec![Some(sum(values))].into())),
+            SeriesEnum::I64(values) => Ok(SeriesEnum::I64(vec![Some(sum(values))].into())),
+            SeriesEnum::I128(values) => Ok(SeriesEnum::I128(vec![Some(sum(values))].into())),
+            SeriesEnum::F32(values) => Ok(SeriesEnum::F32(vec![Some(sum(values))].into())),
+            SeriesEnum::F64(values) => Ok(SeriesEnum::F64(vec![Some(sum(values))].into())),
+            SeriesEnum::Bool(_) | SeriesEnum::Any(_) => Err("Cannot sum this type"),
+        }
     }
 }
 
-impl SeriesTrait for Vec<Option<bool>> {
+impl SeriesTrait for SeriesEnum {
     fn len(&self) -> usize {
-        self.len()
+        apply_series!(self, series => series.len())
     }
 
     fn is_empty(&self) -> bool {
-        self.is_empty()
+        apply_series!(self, series => series.is_empty())
     }
 
-    fn bool(&self) -> ViewResult<bool> {
-        Ok(&self[..])
+    fn extend_from_words(&mut self, words: Words) {
+        apply_series!(self, series => series.extend_from_words(words))
     }
 
-    fn extend_from_words(&mut self, bytes: Words) {
-        bytes.into_iter().for_each(|words| {
-            let el = bytes_to_bool(words);
-            self.push(el);
-        });
+    fn rechunk(&mut self) {
+        apply_series!(self, series => SeriesTrait::rechunk(series))
     }
 
-    fn join(&self, offset: usize, size: usize) -> String {
-        self.iter()
-            .skip(offset)
-            .take(size)
-            .map(|opt| opt.map_or("".into(), |b| b.to_string()))
-            .intersperse(DELIMITER_TOKEN.into())
-            .collect()
+    fn memory_usage(&self) -> MemoryUsage {
+        apply_series!(self, series => SeriesTrait::memory_usage(series))
     }
 
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.iter()
-            .zip(mask)
-            .filter_map(|(opt, mask_el)| {
-                mask_el.then(|| opt.map_or("".into(), |el| el.to_string()))
-            })
-            .skip(offset)
-            .take(size)
-            .intersperse(DELIMITER_TOKEN.into())
-            .collect::<String>()
+    fn get(&self, index: usize) -> Option<AnyValue<'_>> {
+        apply_series!(self, series => series.get(index))
     }
-}
 
-impl SeriesTrait for Vec<Option<String>> {
-    fn len(&self) -> usize {
-        self.len()
+    fn set(&mut self, index: usize, bytes: &[u8]) -> EditResult {
+        apply_series!(self, series => series.set(index, bytes))
     }
 
-    fn is_empty(&self) -> bool {
-        self.is_empty()
+    fn set_value(&mut self, index: usize, value: &AnyValue) -> EditResult {
+        apply_series!(self, series => series.set_value(index, value))
     }
 
-    fn str(&self) -> ViewResult<String> {
-        Ok(&self[..])
+    fn insert_words(&mut self, position: usize, words: Words) {
+        apply_series!(self, series => series.insert_words(position, words))
     }
 
-    fn extend_from_words(&mut self, bytes: Words) {
-        bytes.into_iter().for_each(|word| {
-            let el = String::from_utf8(word.into()).ok();
-            self.push(el);
-        })
+    fn remove_rows(&mut self, mask: &BitSlice) -> Self {
+        match self {
+            SeriesEnum::I32(series) => SeriesEnum::I32(series.remove_rows(mask)),
+            SeriesEnum::I64(series) => SeriesEnum::I64(series.remove_rows(mask)),
+            SeriesEnum::I128(series) => SeriesEnum::I128(series.remove_rows(mask)),
+            SeriesEnum::F32(series) => SeriesEnum::F32(series.remove_rows(mask)),
+            SeriesEnum::F64(series) => SeriesEnum::F64(series.remove_rows(mask)),
+            SeriesEnum::Bool(series) => SeriesEnum::Bool(series.remove_rows(mask)),
+            SeriesEnum::Any(series) => SeriesEnum::Any(series.remove_rows(mask)),
+        }
     }
 
-    fn join(&self, offset: usize, size: usize) -> String {
-        self.iter()
-            .skip(offset)
-            .take(size)
-            .map(|opt| opt.as_deref().unwrap_or_default())
-            .intersperse(DELIMITER_TOKEN)
-            .collect()
+    fn tail(&self, offset: usize) -> Self {
+        match self {
+            SeriesEnum::I32(series) => SeriesEnum::I32(series.tail(offset)),
+            SeriesEnum::I64(series) => SeriesEnum::I64(series.tail(offset)),
+            SeriesEnum::I128(series) => SeriesEnum::I128(series.tail(offset)),
+            SeriesEnum::F32(series) => SeriesEnum::F32(series.tail(offset)),
+            SeriesEnum::F64(series) => SeriesEnum::F64(series.tail(offset)),
+            SeriesEnum::Bool(series) => SeriesEnum::Bool(series.tail(offset)),
+            SeriesEnum::Any(series) => SeriesEnum::Any(series.tail(offset)),
+        }
     }
 
-    fn filter_join(&self, mask: &BitSlice, offset: usize, size: usize) -> String {
-        self.iter()
-            .zip(mask)
-            .filter_map(|(opt, mask_el)| mask_el.then(|| opt.as_deref().unwrap_or_default()))
-            .skip(offset)
-            .take(size)
-            .intersperse(DELIMITER_TOKEN)
-            .collect()
+    fn slice(&self, offset: usize, size: usize) -> Vec<AnyValue<'_>> {
+        apply_series!(self, series => series.slice(offset, size))
     }
 
-    equal_to_series!(str);
-}
+    fn equal_to(&self, other: &SeriesEnum) -> FilterResult {
+        let (left, right) = self.coerce(other)?;
+        apply_series!(left.as_ref(), series => series.equal_to(&right))
+    }
 
-impl SeriesTrait for Vec<Option<i32>> {
-    fn len(&self) -> usize {
-        self.len()
+    fn equal_within(&self, other: &SeriesEnum, tolerance: f64) -> FilterResult<'_> {
+        let (left, right) = self.coerce(other)?;
+        apply_series!(left.as_ref(), series => series.equal_within(&right, tolerance))
     }
 
-    fn is_empty(&self) -> bool {
-        self.is_empty()
+    fn compare(&self, op: CompareOp, other: &SeriesEnum) -> FilterResult<'_> {
+        let (left, right) = self.coerce(other)?;
+        apply_series!(left.as_ref(), series => series.compare(op, &right))
     }
 
-    fn i32(&self) -> ViewResult<i32> {
-        Ok(&self[..])
+    fn between(&self, low: &SeriesEnum, high: &SeriesEnum) -> FilterResult<'_> {
+        let code = common_type(common_type(self.dtype(), low.dtype())?, high.dtype())?;
+        let (low, high) = (low.promote(code)?, high.promote(code)?);
+        apply_series!(self.promote(code)?.as_ref(), series => series.between(&low, &high))
     }
 
-    fn extend_from_words(&mut self, words: Words) {
-        words.into_iter().for_each(|word| {
-            let el = parse(word).ok();
-            self.push(el);
-        })
+    fn validity(&self) -> BitVec {
+        apply_series!(self, series => series.validity())
+    }
+
+    fn distinct(&self) -> Vec<AnyValue<'_>> {
+        apply_series!(self, series => series.distinct())
     }
 
-    join_series!();
-    filter_join!();
-    sum_series!(i32);
-    equal_to_series!(i32);
-    distinct!(i32);
+    fn distinct_at(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        apply_series!(self, series => series.distinct_at(rows))
+    }
+}
+
+fn typed_values<T: Primitive>(values: &[AnyValue]) -> Result<Vec<Option<T>>, WrongType> {
+    values
+        .iter()
+        .map(|value| match value {
+            AnyValue::Null => Ok(None),
+            value => T::from_value(value).map(Some).ok_or(WrongType),
+        })
+        .collect()
+}
+
+pub fn sum<T: Numeric>(values: &PrimitiveSeries<T>) -> T {
+    values
+        .iter()
+        .fold(T::zero(), |acc, x| acc + x.unwrap_or_default())
+}
+
+fn into_value<T: Primitive>(opt: Option<T>) -> AnyValue<'static> {
+    opt.map_or(AnyValue::Null, T::into_value)
 }
 
-impl SeriesTrait for Vec<Option<i64>> {
+impl<T: Primitive> SeriesTrait for PrimitiveSeries<T> {
     fn len(&self) -> usize {
         self.len()
     }
@@ -179,95 +492,197 @@ impl SeriesTrait for Vec<Option<i64>> {
         self.is_empty()
     }
 
-    fn i64(&self) -> ViewResult<i64> {
-        Ok(&self[..])
+    fn extend_from_words(&mut self, words: Words) {
+        self.push_words(words)
     }
 
-    fn extend_from_words(&mut self, words: Words) {
-        words.into_iter().for_each(|word| {
-            let el = parse(word).ok();
-            self.push(el);
-        })
+    fn rechunk(&mut self) {
+        self.rechunk()
     }
 
-    join_series!();
-    sum_series!(i64);
-    equal_to_series!(i64);
-    filter_join!();
-}
+    fn memory_usage(&self) -> MemoryUsage {
+        self.memory_usage()
+    }
 
-impl SeriesTrait for Vec<Option<i128>> {
-    fn len(&self) -> usize {
-        self.len()
+    fn get(&self, index: usize) -> Option<AnyValue<'_>> {
+        self.locate(index)
+            .map(|(chunk, index)| into_value(chunk.get(index)))
     }
 
-    fn is_empty(&self) -> bool {
-        self.is_empty()
+    fn set(&mut self, index: usize, bytes: &[u8]) -> EditResult {
+        let el = match bytes {
+            [] => None,
+            bytes => Some(T::from_bytes(bytes).ok_or(EditError::InvalidValue)?),
+        };
+        let (chunk, index) = self.locate_mut(index).ok_or(EditError::OutOfBounds)?;
+        chunk.set(index, el);
+        Ok(())
     }
 
-    fn i128(&self) -> ViewResult<i128> {
-        Ok(&self[..])
+    fn set_value(&mut self, index: usize, value: &AnyValue) -> EditResult {
+        let el = match value {
+            AnyValue::Null => None,
+            value => Some(T::from_value(value).ok_or(EditError::InvalidValue)?),
+        };
+        let (chunk, index) = self.locate_mut(index).ok_or(EditError::OutOfBounds)?;
+        chunk.set(index, el);
+        Ok(())
     }
 
-    fn extend_from_words(&mut self, words: Words) {
-        words.into_iter().for_each(|word| {
-            let el = parse(word).ok();
-            self.push(el);
-        })
+    fn insert_words(&mut self, position: usize, words: Words) {
+        self.insert(position, PrimitiveArray::from_words(words))
     }
 
-    join_series!();
-    sum_series!(i128);
-    equal_to_series!(i128);
-    filter_join!();
-}
+    fn remove_rows(&mut self, mask: &BitSlice) -> Self {
+        self.remove(mask).into()
+    }
 
-impl SeriesTrait for Vec<Option<f32>> {
-    fn len(&self) -> usize {
-        self.len()
+    fn tail(&self, offset: usize) -> Self {
+        self.tail(offset)
     }
 
-    fn is_empty(&self) -> bool {
-        self.is_empty()
+    fn slice(&self, offset: usize, size: usize) -> Vec<AnyValue<'_>> {
+        self.iter_from(offset).take(size).map(into_value).collect()
     }
 
-    fn f32(&self) -> ViewResult<f32> {
-        Ok(&self[..])
+    fn equal_to(&self, other: &SeriesEnum) -> FilterResult {
+        let set = T::view(other)?
+            .iter()
+            .flatten()
+            .map(T::key)
+            .collect::<HashSet<_>>();
+        let ret = self
+            .iter()
+            .map(|el| el.is_some_and(|el| set.contains(&el.key())))
+            .collect::<BitVec>();
+
+        Ok(ret)
     }
 
-    fn extend_from_words(&mut self, words: Words) {
-        words.into_iter().for_each(|word| {
-            let el = parse(word).ok();
-            self.push(el);
-        })
+    fn compare(&self, op: CompareOp, other: &SeriesEnum) -> FilterResult<'_> {
+        let value = T::view(other)?.iter().next().flatten();
+        Ok(op.mask(self.iter(), value))
     }
 
-    join_series!();
-    sum_series!(f32);
-    filter_join!();
-}
+    fn equal_within(&self, other: &SeriesEnum, tolerance: f64) -> FilterResult<'_> {
+        let value = match T::view(other)?.iter().next().flatten() {
+            Some(value) => Some(value.into_value().to_f64().ok_or(WrongType)?),
+            None => None,
+        };
+        let ret = self
+            .iter()
+            .map(
+                |el| match (el.and_then(|el| el.into_value().to_f64()), value) {
+                    (Some(el), Some(value)) => (el - value).abs() <= tolerance,
+                    _ => false,
+                },
+            )
+            .collect();
 
-impl SeriesTrait for Vec<Option<f64>> {
-    fn len(&self) -> usize {
-        self.len()
+        Ok(ret)
     }
 
-    fn is_empty(&self) -> bool {
-        self.is_empty()
+    fn between(&self, low: &SeriesEnum, high: &SeriesEnum) -> FilterResult<'_> {
+        let low = T::view(low)?.iter().next().flatten();
+        let high = T::view(high)?.iter().next().flatten();
+        Ok(range_mask(self.iter(), low, high))
     }
 
-    fn f64(&self) -> ViewResult<f64> {
-        Ok(&self[..])
+    fn validity(&self) -> BitVec {
+        self.validity()
     }
 
-    fn extend_from_words(&mut self, words: Words) {
-        words.into_iter().for_each(|word| {
-            let el = parse(word).ok();
-            self.push(el);
-        })
+    fn distinct(&self) -> Vec<AnyValue<'_>> {
+        let mut seen = HashSet::new();
+        self.iter()
+            .filter(|el| seen.insert(el.map(T::key)))
+            .map(into_value)
+            .collect()
     }
 
-    join_series!();
-    sum_series!(f64);
-    filter_join!();
+    fn distinct_at(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        let mut seen = HashSet::new();
+        rows.iter()
+            .map(|&row| self.locate(row).and_then(|(chunk, index)| chunk.get(index)))
+            .filter(|el| seen.insert(el.map(T::key)))
+            .map(into_value)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{value::AnyValue, CompareOp, SeriesEnum, SeriesTrait};
+    use crate::{type_parser::Codes, Words};
+
+    #[test]
+    fn generic_kernels() {
+        let series = SeriesEnum::F64(vec![Some(1.5), None, Some(1.5), Some(-2.0)].into());
+        assert_eq!(
+            series.distinct(),
+            vec![AnyValue::F64(1.5), AnyValue::Null, AnyValue::F64(-2.0)]
+        );
+        assert_eq!(series.join(1, 3), "DELIMITER_TOKEN1.5DELIMITER_TOKEN-2");
+
+        let other = SeriesEnum::F64(vec![Some(1.5)].into());
+        let mask = series.equal_to(&other).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0, 2]);
+
+        let integers = SeriesEnum::I32(vec![Some(-2)].into());
+        let mask = series.equal_to(&integers).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![3]);
+        let wrong = SeriesEnum::Bool(vec![Some(true)].into());
+        assert!(series.equal_to(&wrong).is_err());
+
+        let near = SeriesEnum::F64(vec![Some(1.45)].into());
+        let mask = series.equal_within(&near, 0.1).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0, 2]);
+        assert!(series.equal_within(&near, 0.01).unwrap().not_any());
+
+        let mask = series.compare(CompareOp::LessEqual, &other).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0, 2, 3]);
+        let mask = series.compare(CompareOp::NotEqual, &other).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![3]);
+
+        let mut words = Words::default();
+        ["b", "a", "c"]
+            .iter()
+            .for_each(|name| words.extend(name.as_bytes()));
+        let names = SeriesEnum::from_words(Codes::Any, words).unwrap();
+        let mut words = Words::default();
+        words.extend(b"b");
+        let value = SeriesEnum::from_words(Codes::Any, words).unwrap();
+        let mask = names.compare(CompareOp::Greater, &value).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![2]);
+        assert!(names.equal_within(&value, 1.0).is_err());
+    }
+
+    #[test]
+    fn equality() {
+        // a null value never matches, not even null cells
+        let flags = SeriesEnum::Bool(vec![Some(true), None, Some(false)].into());
+        let other = SeriesEnum::Bool(vec![Some(false), None].into());
+        let mask = flags.equal_to(&other).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![2]);
+
+        let floats = SeriesEnum::F32(vec![Some(0.5), Some(-0.0), Some(2.0)].into());
+        let other = SeriesEnum::F32(vec![Some(0.0)].into());
+        let mask = floats.equal_to(&other).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![1]);
+        let other = SeriesEnum::F32(vec![Some(0.4)].into());
+        assert!(floats.equal_to(&other).unwrap().not_any());
+        let mask = floats.equal_within(&other, 0.1).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0]);
+
+        // integers compare across widths, values out of range match nothing
+        let wide = SeriesEnum::I64(vec![Some(3), Some(i64::MAX), Some(-1)].into());
+        let narrow = SeriesEnum::I32(vec![Some(-1), Some(7)].into());
+        let mask = wide.equal_to(&narrow).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![2]);
+        let mask = narrow.equal_to(&wide).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![0]);
+        let huge = SeriesEnum::I128(vec![Some(i128::MAX), Some(7)].into());
+        let mask = narrow.equal_to(&huge).unwrap();
+        assert_eq!(mask.iter_ones().collect::<Vec<_>>(), vec![1]);
+    }
 }
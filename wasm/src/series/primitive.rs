@@ -0,0 +1,135 @@
+use bitvec::{prelude::BitVec, slice::BitSlice};
+
+use crate::{memory::MemoryUsage, Words};
+
+use super::Primitive;
+
+/// Fixed width values laid out contiguously next to a validity bitmap, so the
+/// values buffer can be handed to JS as a typed array without copying.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PrimitiveArray<T> {
+    values: Vec<T>,
+    validity: BitVec,
+}
+
+impl<T: Primitive> PrimitiveArray<T> {
+    pub fn from_words(words: Words) -> Self {
+        words.into_iter().map(T::from_bytes).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn push(&mut self, el: Option<T>) {
+        self.values.push(el.unwrap_or_default());
+        self.validity.push(el.is_some());
+    }
+
+    pub fn set(&mut self, index: usize, el: Option<T>) {
+        self.values[index] = el.unwrap_or_default();
+        self.validity.set(index, el.is_some());
+    }
+
+    pub fn append(&mut self, mut other: PrimitiveArray<T>) {
+        self.values.append(&mut other.values);
+        self.validity.extend_from_bitslice(&other.validity);
+    }
+
+    pub fn split_off(&mut self, at: usize) -> Self {
+        Self {
+            values: self.values.split_off(at),
+            validity: self.validity.split_off(at),
+        }
+    }
+
+    /// Splits the rows into those left unset in `mask` and those set in it.
+    pub fn partition(&self, mask: &BitSlice) -> (Self, Self) {
+        let mut kept = Self::default();
+        let mut taken = Self::default();
+        self.iter()
+            .zip(mask)
+            .for_each(|(el, remove)| match *remove {
+                true => taken.push(el),
+                false => kept.push(el),
+            });
+        (kept, taken)
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::values(self.values.capacity() * std::mem::size_of::<T>())
+            + MemoryUsage::validity(self.validity.capacity())
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.validity
+            .get(index)
+            .and_then(|valid| valid.then(|| self.values[index]))
+    }
+
+    /// Raw values, null slots hold `T::default()`.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn validity(&self) -> &BitSlice {
+        &self.validity
+    }
+
+    /// Validity bitmap packed least significant bit first, one bit per row.
+    pub fn validity_bytes(&self) -> &[u8] {
+        let raw = self.validity.as_raw_slice();
+        let n_bytes = self.validity.len().div_ceil(8);
+        // SAFETY: `raw` owns at least `n_bytes` initialised bytes and u8 has no alignment
+        // requirement. Wasm is little endian, so bytes follow the Lsb0 bit order.
+        unsafe { std::slice::from_raw_parts(raw.as_ptr() as *const u8, n_bytes) }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Option<T>> + '_ {
+        self.iter_from(0)
+    }
+
+    pub fn iter_from(&self, start: usize) -> impl Iterator<Item = Option<T>> + '_ {
+        self.values[start..]
+            .iter()
+            .zip(self.validity[start..].iter())
+            .map(|(&value, valid)| valid.then_some(value))
+    }
+}
+
+impl<T: Primitive> FromIterator<Option<T>> for PrimitiveArray<T> {
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        let mut ret = Self::default();
+        iter.into_iter().for_each(|el| ret.push(el));
+        ret
+    }
+}
+
+impl<T: Primitive> From<Vec<Option<T>>> for PrimitiveArray<T> {
+    fn from(values: Vec<Option<T>>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PrimitiveArray;
+
+    #[test]
+    fn contiguous_layout() {
+        let mut array = PrimitiveArray::from(vec![Some(1.5), None, Some(3.0)]);
+        array.append(PrimitiveArray::from(vec![None; 6]));
+
+        assert_eq!(
+            array.values(),
+            &[1.5, 0.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(array.validity_bytes(), &[0b101, 0]);
+        assert_eq!(array.get(1), None);
+        assert_eq!(array.iter().flatten().sum::<f64>(), 4.5);
+    }
+}
@@ -0,0 +1,278 @@
+use std::{collections::HashSet, str};
+
+use bitvec::{prelude::BitVec, slice::BitSlice};
+
+use crate::{memory::MemoryUsage, Words};
+
+use super::{
+    chunked::ChunkedArray,
+    errors::{EditError, EditResult, FilterResult, WrongType},
+    range_mask,
+    value::AnyValue,
+    CompareOp, SeriesEnum, SeriesTrait,
+};
+
+/// String series backed by a single byte buffer plus offsets, as produced by
+/// the csv reader. A cleared validity bit marks a cell that is not valid utf8.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Utf8Series {
+    words: Words,
+    validity: BitVec,
+}
+
+impl Utf8Series {
+    pub fn from_words(words: Words) -> Self {
+        let validity = words
+            .into_iter()
+            .map(|bytes| str::from_utf8(bytes).is_ok())
+            .collect();
+        Self { words, validity }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        if !*self.validity.get(index)? {
+            return None;
+        }
+        // SAFETY: the validity bit is only set after `str::from_utf8` succeeded
+        self.words
+            .get(index)
+            .map(|bytes| unsafe { str::from_utf8_unchecked(bytes) })
+    }
+
+    pub fn push(&mut self, el: Option<&str>) {
+        self.words.extend(el.unwrap_or_default().as_bytes());
+        self.validity.push(el.is_some());
+    }
+
+    pub fn set(&mut self, index: usize, el: Option<&str>) {
+        self.words.replace(index, el.unwrap_or_default().as_bytes());
+        self.validity.set(index, el.is_some());
+    }
+
+    pub fn append(&mut self, other: Utf8Series) {
+        self.words.append(other.words);
+        self.validity.extend_from_bitslice(&other.validity);
+    }
+
+    pub fn split_off(&mut self, at: usize) -> Self {
+        Self {
+            words: self.words.split_off(at),
+            validity: self.validity.split_off(at),
+        }
+    }
+
+    /// Splits the rows into those left unset in `mask` and those set in it.
+    pub fn partition(&self, mask: &BitSlice) -> (Self, Self) {
+        let mut kept = Self::default();
+        let mut taken = Self::default();
+        self.words
+            .into_iter()
+            .zip(self.validity.iter())
+            .zip(mask)
+            .for_each(|((bytes, valid), remove)| {
+                let side = if *remove { &mut taken } else { &mut kept };
+                side.words.extend(bytes);
+                side.validity.push(*valid);
+            });
+        (kept, taken)
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::strings(self.words.heap_size())
+            + MemoryUsage::validity(self.validity.capacity())
+    }
+
+    pub fn validity(&self) -> &BitSlice {
+        &self.validity
+    }
+
+    pub fn iter(&self) -> Utf8Iter<'_> {
+        Utf8Iter {
+            series: self,
+            cursor: 0,
+        }
+    }
+}
+
+pub struct Utf8Iter<'a> {
+    series: &'a Utf8Series,
+    cursor: usize,
+}
+
+impl<'a> Iterator for Utf8Iter<'a> {
+    type Item = Option<&'a str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor >= self.series.len() {
+            return None;
+        }
+
+        let ret = self.series.get(self.cursor);
+        self.cursor += 1;
+        Some(ret)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.series.len().saturating_sub(self.cursor);
+        (remaining, Some(remaining))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.cursor = self.cursor.saturating_add(n);
+        self.next()
+    }
+}
+
+impl SeriesTrait for ChunkedArray<Utf8Series> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn extend_from_words(&mut self, words: Words) {
+        self.push_words(words)
+    }
+
+    fn rechunk(&mut self) {
+        self.rechunk()
+    }
+
+    fn memory_usage(&self) -> MemoryUsage {
+        self.memory_usage()
+    }
+
+    fn get(&self, index: usize) -> Option<AnyValue<'_>> {
+        self.locate(index)
+            .map(|(chunk, index)| AnyValue::from(chunk.get(index)))
+    }
+
+    fn set(&mut self, index: usize, bytes: &[u8]) -> EditResult {
+        let (chunk, index) = self.locate_mut(index).ok_or(EditError::OutOfBounds)?;
+        let text = str::from_utf8(bytes).map_err(|_| EditError::InvalidValue)?;
+        chunk.set(index, Some(text));
+        Ok(())
+    }
+
+    fn set_value(&mut self, index: usize, value: &AnyValue) -> EditResult {
+        let el = match value {
+            AnyValue::Null => None,
+            AnyValue::Str(text) => Some(text.as_ref()),
+            _ => return Err(EditError::InvalidValue),
+        };
+        let (chunk, index) = self.locate_mut(index).ok_or(EditError::OutOfBounds)?;
+        chunk.set(index, el);
+        Ok(())
+    }
+
+    fn insert_words(&mut self, position: usize, words: Words) {
+        self.insert(position, Utf8Series::from_words(words))
+    }
+
+    fn remove_rows(&mut self, mask: &BitSlice) -> Self {
+        self.remove(mask).into()
+    }
+
+    fn tail(&self, offset: usize) -> Self {
+        self.tail(offset)
+    }
+
+    fn slice(&self, offset: usize, size: usize) -> Vec<AnyValue<'_>> {
+        self.iter_from(offset)
+            .take(size)
+            .map(AnyValue::from)
+            .collect()
+    }
+
+    fn equal_to(&self, other: &SeriesEnum) -> FilterResult {
+        let set = other.str()?.iter().flatten().collect::<HashSet<_>>();
+        let ret = self
+            .iter()
+            .map(|el| el.is_some_and(|el| set.contains(el)))
+            .collect::<BitVec>();
+
+        Ok(ret)
+    }
+
+    fn compare(&self, op: CompareOp, other: &SeriesEnum) -> FilterResult<'_> {
+        let value = other.str()?.iter().next().flatten();
+        Ok(op.mask(self.iter(), value))
+    }
+
+    fn equal_within(&self, _other: &SeriesEnum, _tolerance: f64) -> FilterResult<'_> {
+        Err(WrongType)
+    }
+
+    fn between(&self, low: &SeriesEnum, high: &SeriesEnum) -> FilterResult<'_> {
+        let low = low.str()?.iter().next().flatten();
+        let high = high.str()?.iter().next().flatten();
+        Ok(range_mask(self.iter(), low, high))
+    }
+
+    fn validity(&self) -> BitVec {
+        self.validity()
+    }
+
+    fn distinct(&self) -> Vec<AnyValue<'_>> {
+        let mut seen = HashSet::new();
+        self.iter()
+            .filter(|&el| seen.insert(el))
+            .map(AnyValue::from)
+            .collect()
+    }
+
+    fn distinct_at(&self, rows: &[usize]) -> Vec<AnyValue<'_>> {
+        let mut seen = HashSet::new();
+        rows.iter()
+            .map(|&row| self.locate(row).and_then(|(chunk, index)| chunk.get(index)))
+            .filter(|&el| seen.insert(el))
+            .map(AnyValue::from)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Utf8Series;
+    use crate::{
+        series::{chunked::ChunkedArray, value::AnyValue, SeriesTrait},
+        Words,
+    };
+
+    #[test]
+    fn zero_copy_view() {
+        let mut words = Words::default();
+        words.extend(b"Flareon");
+        words.extend(&[0xff, 0xfe]);
+        words.extend(b"");
+
+        let chunk = Utf8Series::from_words(words);
+        let view = chunk.iter().collect::<Vec<_>>();
+        assert_eq!(view, vec![Some("Flareon"), None, Some("")]);
+
+        let mut series = ChunkedArray::from(chunk);
+        let mut tail = Words::default();
+        tail.extend(b"Jolteon");
+        series.extend_from_words(tail);
+
+        assert_eq!(series.len(), 4);
+        assert_eq!(series.join(2, 2), "DELIMITER_TOKENJolteon");
+        assert_eq!(
+            series.slice(0, 2),
+            vec![AnyValue::Str("Flareon".into()), AnyValue::Null]
+        );
+
+        SeriesTrait::rechunk(&mut series);
+        assert_eq!(series.chunks()[0].get(3), Some("Jolteon"));
+    }
+}
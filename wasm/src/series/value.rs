@@ -0,0 +1,151 @@
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+};
+
+use js_sys::BigInt;
+use wasm_bindgen::JsValue;
+
+use super::DELIMITER_TOKEN;
+
+const SAFE_INTEGERS: RangeInclusive<i64> = -((1 << 53) - 1)..=(1 << 53) - 1;
+
+/// Single typed cell, borrowing string data from the series it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnyValue<'a> {
+    Null,
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Str(Cow<'a, str>),
+}
+
+impl<'a> AnyValue<'a> {
+    pub fn is_null(&self) -> bool {
+        matches!(self, AnyValue::Null)
+    }
+
+    pub fn is_nan(&self) -> bool {
+        match *self {
+            AnyValue::F32(v) => v.is_nan(),
+            AnyValue::F64(v) => v.is_nan(),
+            _ => false,
+        }
+    }
+
+    pub fn into_static(self) -> AnyValue<'static> {
+        match self {
+            AnyValue::Null => AnyValue::Null,
+            AnyValue::Bool(v) => AnyValue::Bool(v),
+            AnyValue::I32(v) => AnyValue::I32(v),
+            AnyValue::I64(v) => AnyValue::I64(v),
+            AnyValue::I128(v) => AnyValue::I128(v),
+            AnyValue::F32(v) => AnyValue::F32(v),
+            AnyValue::F64(v) => AnyValue::F64(v),
+            AnyValue::Str(v) => AnyValue::Str(Cow::Owned(v.into_owned())),
+        }
+    }
+
+    /// Numeric cells widened to `f64`, `None` for nulls, booleans and strings.
+    pub fn to_f64(&self) -> Option<f64> {
+        match *self {
+            AnyValue::I32(v) => Some(v.into()),
+            AnyValue::I64(v) => Some(v as f64),
+            AnyValue::I128(v) => Some(v as f64),
+            AnyValue::F32(v) => Some(v.into()),
+            AnyValue::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Cells are only ordered against cells of the same type.
+impl<'a> PartialOrd for AnyValue<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (AnyValue::Null, AnyValue::Null) => Some(Ordering::Equal),
+            (AnyValue::Bool(l), AnyValue::Bool(r)) => l.partial_cmp(r),
+            (AnyValue::I32(l), AnyValue::I32(r)) => l.partial_cmp(r),
+            (AnyValue::I64(l), AnyValue::I64(r)) => l.partial_cmp(r),
+            (AnyValue::I128(l), AnyValue::I128(r)) => l.partial_cmp(r),
+            (AnyValue::F32(l), AnyValue::F32(r)) => l.partial_cmp(r),
+            (AnyValue::F64(l), AnyValue::F64(r)) => l.partial_cmp(r),
+            (AnyValue::Str(l), AnyValue::Str(r)) => l.partial_cmp(r),
+            _ => None,
+        }
+    }
+}
+
+/// Floats hash by bit pattern with `-0.0` folded onto `0.0`, matching `PartialEq`.
+impl<'a> Hash for AnyValue<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            AnyValue::Null => {}
+            AnyValue::Bool(v) => v.hash(state),
+            AnyValue::I32(v) => v.hash(state),
+            AnyValue::I64(v) => v.hash(state),
+            AnyValue::I128(v) => v.hash(state),
+            AnyValue::F32(v) => (v + 0.0).to_bits().hash(state),
+            AnyValue::F64(v) => (v + 0.0).to_bits().hash(state),
+            AnyValue::Str(v) => v.hash(state),
+        }
+    }
+}
+
+impl<'a> From<Option<&'a str>> for AnyValue<'a> {
+    fn from(opt: Option<&'a str>) -> Self {
+        opt.map_or(AnyValue::Null, |s| AnyValue::Str(Cow::Borrowed(s)))
+    }
+}
+
+impl<'a> fmt::Display for AnyValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyValue::Null => Ok(()),
+            AnyValue::Bool(v) => write!(f, "{}", v),
+            AnyValue::I32(v) => write!(f, "{}", v),
+            AnyValue::I64(v) => write!(f, "{}", v),
+            AnyValue::I128(v) => write!(f, "{}", v),
+            AnyValue::F32(v) => write!(f, "{}", v),
+            AnyValue::F64(v) => write!(f, "{}", v),
+            AnyValue::Str(v) => f.write_str(v),
+        }
+    }
+}
+
+/// Integers outside of the safe `Number` range are handed over as `BigInt`.
+impl<'a> From<AnyValue<'a>> for JsValue {
+    fn from(value: AnyValue<'a>) -> Self {
+        match value {
+            AnyValue::Null => JsValue::NULL,
+            AnyValue::Bool(v) => JsValue::from_bool(v),
+            AnyValue::I32(v) => JsValue::from_f64(v.into()),
+            AnyValue::I64(v) if SAFE_INTEGERS.contains(&v) => JsValue::from_f64(v as f64),
+            AnyValue::I64(v) => BigInt::from(v).into(),
+            AnyValue::I128(v) => match i64::try_from(v) {
+                Ok(v) => AnyValue::I64(v).into(),
+                Err(_) => BigInt::from(v).into(),
+            },
+            // widening through the shortest decimal keeps 1.2f32 from showing up as 1.2000000476837158
+            AnyValue::F32(v) => JsValue::from_f64(v.to_string().parse().unwrap_or(v.into())),
+            AnyValue::F64(v) => JsValue::from_f64(v),
+            AnyValue::Str(v) => JsValue::from_str(&v),
+        }
+    }
+}
+
+/// Legacy rendering of a run of cells as a single `DELIMITER_TOKEN` separated string.
+pub fn join_values<'a>(values: impl IntoIterator<Item = AnyValue<'a>>) -> String {
+    values
+        .into_iter()
+        .map(|value| value.to_string())
+        .intersperse(DELIMITER_TOKEN.into())
+        .collect()
+}
@@ -0,0 +1,263 @@
+use std::cmp::Ordering;
+
+use bitvec::slice::BitSlice;
+
+use crate::{
+    series::{errors::EditError, value::AnyValue},
+    Frame,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+impl SortKey {
+    /// Nulls are placed according to `nulls_first` whatever the direction, NaN
+    /// always follows the numbers.
+    pub(crate) fn compare(&self, left: &AnyValue, right: &AnyValue) -> Ordering {
+        let nulls = match self.nulls_first {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        };
+
+        match (left.is_null(), right.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => nulls,
+            (false, true) => nulls.reverse(),
+            (false, false) if left.is_nan() || right.is_nan() => left.is_nan().cmp(&right.is_nan()),
+            (false, false) => {
+                let ord = left.partial_cmp(right).unwrap_or(Ordering::Equal);
+                match self.descending {
+                    true => ord.reverse(),
+                    false => ord,
+                }
+            }
+        }
+    }
+}
+
+impl Frame {
+    /// Orders the rows by `keys` without moving any data. The order is kept up
+    /// to date as rows are streamed in or edited.
+    pub fn sort(&mut self, keys: Vec<SortKey>) -> Result<(), EditError> {
+        for key in keys.iter() {
+            self.column_index(&key.column)?;
+        }
+
+        self.sort_keys = keys;
+        self.resort();
+        self.track_memory();
+        Ok(())
+    }
+
+    pub fn clear_sort(&mut self) {
+        self.sort_keys.clear();
+        self.resort();
+        self.track_memory();
+    }
+
+    pub fn sort_keys(&self) -> &[SortKey] {
+        &self.sort_keys
+    }
+
+    /// Rebuilds `index` from the sort keys, keys whose column is gone are dropped.
+    pub(crate) fn resort(&mut self) {
+        self.generation += 1;
+        let columns = &self.columns;
+        self.sort_keys
+            .retain(|key| columns.iter().any(|column| column.name() == key.column));
+        if self.sort_keys.is_empty() {
+            self.index = Vec::new();
+            return;
+        }
+
+        let height = self.height();
+        let keys = self
+            .sort_keys
+            .iter()
+            .map(|key| (key, self.find_by_name(&key.column).slice(0, height)))
+            .collect::<Vec<_>>();
+
+        let mut index = (0..height).collect::<Vec<_>>();
+        index.sort_by(|&left, &right| {
+            keys.iter()
+                .map(|(key, values)| key.compare(&values[left], &values[right]))
+                .find(|ord| ord.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        self.index = index;
+    }
+
+    /// Places the rows stored from `from` onwards, such as a streamed chunk,
+    /// into the current order without sorting the other rows again.
+    pub(crate) fn sort_appended(&mut self, from: usize) {
+        self.generation += 1;
+        if self.sort_keys.is_empty() {
+            return;
+        }
+
+        self.index = {
+            let order = self.row_order();
+            let mut appended = (from..self.height()).collect::<Vec<_>>();
+            appended.sort_by(|&left, &right| order(left, right));
+
+            let mut index = Vec::with_capacity(self.index.len() + appended.len());
+            let mut start = 0;
+            for row in appended {
+                let rest = &self.index[start..];
+                let end = start + rest.partition_point(|&other| order(other, row).is_lt());
+                index.extend_from_slice(&self.index[start..end]);
+                index.push(row);
+                start = end;
+            }
+            index.extend_from_slice(&self.index[start..]);
+            index
+        };
+    }
+
+    /// Moves the row stored at `row` to its place after one of its cells changed.
+    pub(crate) fn reposition(&mut self, row: usize) {
+        self.generation += 1;
+        let Some(at) = self.index.iter().position(|&other| other == row) else {
+            return;
+        };
+
+        self.index.remove(at);
+        let at = {
+            let order = self.row_order();
+            self.index
+                .partition_point(|&other| order(other, row).is_lt())
+        };
+        self.index.insert(at, row);
+    }
+
+    /// Order of two stored rows under the sort keys, ties broken by storage
+    /// position as `resort` does.
+    fn row_order(&self) -> impl Fn(usize, usize) -> Ordering + '_ {
+        let keys = self
+            .sort_keys
+            .iter()
+            .filter_map(|key| Some((key, &self.columns[self.column_index(&key.column).ok()?])))
+            .collect::<Vec<_>>();
+
+        move |left, right| {
+            keys.iter()
+                .map(|(key, column)| {
+                    let value = |row| column.get(row).unwrap_or(AnyValue::Null);
+                    key.compare(&value(left), &value(right))
+                })
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| left.cmp(&right))
+        }
+    }
+
+    /// Changes whenever rows are added, removed or reordered, so views built on
+    /// the frame know when to rebuild.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Storage position of the row displayed at `row`.
+    pub fn physical_row(&self, row: usize) -> usize {
+        self.index.get(row).copied().unwrap_or(row)
+    }
+
+    /// Storage positions of the rows displayed from `offset`, restricted to the
+    /// rows set in `mask` when given.
+    pub fn view_rows(&self, mask: Option<&BitSlice>, offset: usize, size: usize) -> Vec<usize> {
+        let matches = |row: &usize| mask.is_none_or(|mask| mask.get(*row).is_some_and(|bit| *bit));
+        match self.index.is_empty() {
+            true => (0..self.height())
+                .filter(matches)
+                .skip(offset)
+                .take(size)
+                .collect(),
+            false => self
+                .index
+                .iter()
+                .copied()
+                .filter(matches)
+                .skip(offset)
+                .take(size)
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SortKey;
+    use crate::{filter::Filter, ChunkFromJsBytes, Frame};
+
+    fn key(column: &str, descending: bool, nulls_first: bool) -> SortKey {
+        SortKey {
+            column: column.into(),
+            descending,
+            nulls_first,
+        }
+    }
+
+    #[test]
+    fn sort() {
+        let bytes = "Name,Level\nFlareon,25\nVaporeon,\nJolteon,30\nEevee,25".as_bytes();
+        let chunk = ChunkFromJsBytes::from_bytes(bytes).with_header(true).read();
+        let mut frame = Frame::new();
+        frame.new_from_entry(chunk);
+
+        frame
+            .sort(vec![key("Level", true, false), key("Name", false, false)])
+            .unwrap();
+        assert_eq!(frame.index, vec![2, 3, 0, 1]);
+        assert_eq!(
+            frame.slice_joined_values(0, 4)[0],
+            "JolteonDELIMITER_TOKENEeveeDELIMITER_TOKENFlareonDELIMITER_TOKENVaporeon"
+        );
+
+        frame.sort(vec![key("Level", false, true)]).unwrap();
+        assert_eq!(frame.index, vec![1, 0, 3, 2]);
+
+        let mut filter = Filter::default();
+        filter.add_equalto_filter(&frame, b"25", "Level").unwrap();
+        assert_eq!(filter.join(&frame, 0, 5)[0], "FlareonDELIMITER_TOKENEevee");
+
+        // edits address rows as displayed and keep the order up to date
+        frame.set_cell(0, "Level", "40").unwrap();
+        assert_eq!(frame.index, vec![0, 3, 2, 1]);
+        frame.insert_rows(0, b"Espeon,").unwrap();
+        assert_eq!(frame.physical_row(0), 4);
+
+        frame.clear_sort();
+        assert!(frame.index.is_empty());
+        assert_eq!(frame.view_rows(None, 3, 5), vec![3, 4]);
+    }
+
+    #[test]
+    fn sort_streamed() {
+        let mut frame = Frame::new();
+        frame.append(b"Name,Level\nFlareon,25\nVaporeon,\nJolteon,30\n", true);
+        frame
+            .sort(vec![key("Level", true, true), key("Name", false, false)])
+            .unwrap();
+
+        frame.append(b"Eevee,25\nEspeon,30\nUmbreon,\nLeafeon,60\n", false);
+        frame.append(b"Glaceon,5\nSylveon,25", false);
+        frame.append_remainder();
+        let merged = frame.index.clone();
+        frame.resort();
+        assert_eq!(merged, frame.index);
+        assert_eq!(
+            frame.slice_joined_values(0, 4)[0],
+            "UmbreonDELIMITER_TOKENVaporeonDELIMITER_TOKENLeafeonDELIMITER_TOKENEspeon"
+        );
+
+        // an edited row moves to the place a full sort gives it
+        frame.set_cell(0, "Level", "26").unwrap();
+        frame.set_cell(7, "Name", "Flareon").unwrap();
+        let moved = frame.index.clone();
+        frame.resort();
+        assert_eq!(moved, frame.index);
+    }
+}
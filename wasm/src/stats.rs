@@ -0,0 +1,266 @@
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use wasm_bindgen::prelude::*;
+
+use crate::series::{
+    chunked::ChunkedArray, utf8::Utf8Series, value::AnyValue, Primitive, PrimitiveSeries,
+    SeriesEnum, SeriesTrait,
+};
+
+const SKETCH_BITS: u32 = 10;
+const SKETCH_REGISTERS: usize = 1 << SKETCH_BITS;
+
+/// HyperLogLog sketch estimating the number of distinct values seen.
+#[derive(Clone, Debug, Default)]
+struct DistinctSketch {
+    registers: Vec<u8>,
+}
+
+impl DistinctSketch {
+    fn insert(&mut self, value: &AnyValue) {
+        if self.registers.is_empty() {
+            self.registers = vec![0; SKETCH_REGISTERS];
+        }
+
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - SKETCH_BITS)) as usize;
+        let rank = ((hash << SKETCH_BITS).leading_zeros() + 1).min(64 - SKETCH_BITS + 1) as u8;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    fn estimate(&self) -> usize {
+        if self.registers.is_empty() {
+            return 0;
+        }
+
+        let m = SKETCH_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let harmonic: f64 = self
+            .registers
+            .iter()
+            .map(|&rank| 2f64.powi(-(rank as i32)))
+            .sum();
+        let raw = alpha * m * m / harmonic;
+
+        let zeros = self.registers.iter().filter(|&&rank| rank == 0).count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as usize
+    }
+}
+
+/// Column summary maintained while chunks stream in.
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    count: usize,
+    null_count: usize,
+    min: Option<AnyValue<'static>>,
+    max: Option<AnyValue<'static>>,
+    sum: Option<f64>,
+    sketch: DistinctSketch,
+}
+
+impl Stats {
+    pub fn from_series(series: &SeriesEnum) -> Self {
+        let mut stats = Self::default();
+        stats.update(series, 0);
+        stats
+    }
+
+    /// Folds the rows of `series` starting at `offset` into the summary.
+    pub fn update(&mut self, series: &SeriesEnum, offset: usize) {
+        match series {
+            SeriesEnum::I32(series) => self.update_primitive(series, offset),
+            SeriesEnum::I64(series) => self.update_primitive(series, offset),
+            SeriesEnum::I128(series) => self.update_primitive(series, offset),
+            SeriesEnum::F32(series) => self.update_primitive(series, offset),
+            SeriesEnum::F64(series) => self.update_primitive(series, offset),
+            SeriesEnum::Bool(series) => self.update_primitive(series, offset),
+            SeriesEnum::Any(series) => self.update_utf8(series, offset),
+        }
+    }
+
+    fn update_primitive<T: Primitive>(&mut self, series: &PrimitiveSeries<T>, offset: usize) {
+        series
+            .iter_from(offset)
+            .for_each(|el| self.push(el.map_or(AnyValue::Null, T::into_value)));
+    }
+
+    fn update_utf8(&mut self, series: &ChunkedArray<Utf8Series>, offset: usize) {
+        series
+            .iter_from(offset)
+            .for_each(|el| self.push(AnyValue::from(el)));
+    }
+
+    fn push(&mut self, value: AnyValue) {
+        if value.is_null() {
+            self.null_count += 1;
+            return;
+        }
+
+        self.count += 1;
+        if let Some(number) = value.to_f64() {
+            *self.sum.get_or_insert(0.0) += number;
+        }
+        self.sketch.insert(&value);
+
+        if self.min.as_ref().is_none_or(|min| value < *min) {
+            self.min = Some(value.clone().into_static());
+        }
+        if self.max.as_ref().is_none_or(|max| value > *max) {
+            self.max = Some(value.into_static());
+        }
+    }
+
+    /// Swaps `old` for the cell now stored at `index`. Bounds are rescanned only
+    /// when the replaced cell was the minimum or the maximum, the distinct
+    /// estimate never forgets values.
+    pub fn replace(&mut self, series: &SeriesEnum, index: usize, old: &AnyValue) {
+        let new = series.get(index).unwrap_or(AnyValue::Null);
+
+        match old.to_f64() {
+            _ if old.is_null() => self.null_count -= 1,
+            Some(number) => {
+                self.count -= 1;
+                self.sum = self.sum.map(|sum| sum - number);
+            }
+            None => self.count -= 1,
+        }
+
+        let was_bound = !old.is_null()
+            && (self.min.as_ref() == Some(old) || self.max.as_ref() == Some(old))
+            && *old != new;
+        self.push(new);
+
+        if was_bound {
+            let rescan = Self::from_series(series);
+            self.min = rescan.min;
+            self.max = rescan.max;
+        }
+    }
+
+    pub fn min(&self) -> Option<&AnyValue<'static>> {
+        self.min.as_ref()
+    }
+
+    pub fn max(&self) -> Option<&AnyValue<'static>> {
+        self.max.as_ref()
+    }
+
+    /// `false` when `value` is known to fall outside of `[min, max]`. Values of
+    /// another type cannot be ruled out.
+    pub fn may_contain(&self, value: &AnyValue) -> bool {
+        if value.is_null() {
+            return self.null_count > 0;
+        }
+
+        match (&self.min, &self.max) {
+            (Some(min), Some(max)) => !matches!(
+                (value.partial_cmp(min), value.partial_cmp(max)),
+                (Some(Ordering::Less), _) | (_, Some(Ordering::Greater))
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Stats {
+    /// Number of non null cells.
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[wasm_bindgen(getter = nullCount)]
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    #[wasm_bindgen(getter = min)]
+    pub fn js_min(&self) -> JsValue {
+        self.min.clone().map_or(JsValue::NULL, JsValue::from)
+    }
+
+    #[wasm_bindgen(getter = max)]
+    pub fn js_max(&self) -> JsValue {
+        self.max.clone().map_or(JsValue::NULL, JsValue::from)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sum(&self) -> Option<f64> {
+        self.sum
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> Option<f64> {
+        self.sum
+            .filter(|_| self.count > 0)
+            .map(|sum| sum / self.count as f64)
+    }
+
+    #[wasm_bindgen(getter = distinctEstimate)]
+    pub fn distinct_estimate(&self) -> usize {
+        self.sketch.estimate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Stats;
+    use crate::series::{value::AnyValue, SeriesEnum, SeriesTrait};
+
+    #[test]
+    fn incremental() {
+        let series = SeriesEnum::I32(vec![Some(3), None, Some(-1), Some(3)].into());
+        let stats = Stats::from_series(&series);
+
+        assert_eq!(stats.count(), 3);
+        assert_eq!(stats.null_count(), 1);
+        assert_eq!(stats.min(), Some(&AnyValue::I32(-1)));
+        assert_eq!(stats.max(), Some(&AnyValue::I32(3)));
+        assert_eq!(stats.sum(), Some(5.0));
+        assert_eq!(stats.distinct_estimate(), 2);
+
+        assert!(stats.may_contain(&AnyValue::I32(0)));
+        assert!(!stats.may_contain(&AnyValue::I32(4)));
+        assert!(stats.may_contain(&AnyValue::I64(4)));
+    }
+
+    #[test]
+    fn replace() {
+        let mut series = SeriesEnum::F64(vec![Some(1.0), Some(4.0), None].into());
+        let mut stats = Stats::from_series(&series);
+
+        series.set(1, b"2.5").unwrap();
+        stats.replace(&series, 1, &AnyValue::F64(4.0));
+        assert_eq!(stats.max(), Some(&AnyValue::F64(2.5)));
+        assert_eq!(stats.sum(), Some(3.5));
+
+        series.set(2, b"-1").unwrap();
+        stats.replace(&series, 2, &AnyValue::Null);
+        assert_eq!(stats.null_count(), 0);
+        assert_eq!(stats.min(), Some(&AnyValue::F64(-1.0)));
+        assert_eq!(stats.mean(), Some(2.5 / 3.0));
+    }
+
+    #[test]
+    fn distinct_estimate() {
+        let values = (0..10_000).map(|i| Some(i % 2_500)).collect::<Vec<_>>();
+        let stats = Stats::from_series(&SeriesEnum::I64(values.into()));
+        let estimate = stats.distinct_estimate() as f64;
+
+        assert!((estimate - 2_500.0).abs() / 2_500.0 < 0.1);
+    }
+}
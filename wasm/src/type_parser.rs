@@ -1,4 +1,9 @@
-use crate::{series::Numeric, Words};
+use crate::{
+    series::{primitive::PrimitiveArray, utf8::Utf8Series, Numeric},
+    Words,
+};
+
+use std::str::FromStr;
 
 use js_sys::JsString;
 use lazy_static::lazy_static;
@@ -55,6 +60,24 @@ impl From<Codes> for JsString {
     }
 }
 
+impl FromStr for Codes {
+    type Err = ();
+
+    /// Inverse of the names handed to JS through `dtypes`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "Boolean" => Ok(Codes::Boolean),
+            "Int32" => Ok(Codes::Int32),
+            "Int64" => Ok(Codes::Int64),
+            "Int128" => Ok(Codes::Int128),
+            "Float32" => Ok(Codes::Float32),
+            "Float64" => Ok(Codes::Float64),
+            "Any" => Ok(Codes::Any),
+            _ => Err(()),
+        }
+    }
+}
+
 pub enum IntegerTypes {
     Int32(i32),
     Int64(i64),
@@ -136,8 +159,8 @@ pub fn bytes_to_bool(bytes: &[u8]) -> Option<bool> {
     }
 }
 
-pub fn parse_type<T: Numeric + FromLexical>(words: Words) -> Vec<Option<T>> {
-    let mut ret = Vec::new();
+pub fn parse_type<T: Numeric + FromLexical>(words: Words) -> PrimitiveArray<T> {
+    let mut ret = PrimitiveArray::default();
     words.into_iter().for_each(|bytes| {
         let el = parse(bytes).ok();
         ret.push(el);
@@ -145,8 +168,8 @@ pub fn parse_type<T: Numeric + FromLexical>(words: Words) -> Vec<Option<T>> {
     ret
 }
 
-pub fn parse_bool(words: Words) -> Vec<Option<bool>> {
-    let mut ret = Vec::new();
+pub fn parse_bool(words: Words) -> PrimitiveArray<bool> {
+    let mut ret = PrimitiveArray::default();
     words.into_iter().for_each(|bytes| {
         let el = bytes_to_bool(bytes);
         ret.push(el);
@@ -154,11 +177,6 @@ pub fn parse_bool(words: Words) -> Vec<Option<bool>> {
     ret
 }
 
-pub fn parse_utf8(words: Words) -> Vec<Option<String>> {
-    let mut ret = Vec::new();
-    words.into_iter().for_each(|bytes| {
-        let el = String::from_utf8(bytes.into()).ok();
-        ret.push(el);
-    });
-    ret
+pub fn parse_utf8(words: Words) -> Utf8Series {
+    Utf8Series::from_words(words)
 }
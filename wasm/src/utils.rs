@@ -33,7 +33,10 @@ pub trait LendingIterator {
 
 #[allow(clippy::needless_lifetimes)]
 impl<'a, T> LendingIterator for HeaderFillerGenerator<'a, T> {
-    type Item<'t> = &'t [u8] where Self: 't;
+    type Item<'t>
+        = &'t [u8]
+    where
+        Self: 't;
 
     fn next<'t>(&'t mut self) -> Option<Self::Item<'t>> {
         if self.cycles > self.symbols.len() {